@@ -2,7 +2,7 @@ use std::{fs, path::Path};
 
 use serde::Deserialize;
 
-use crate::util::serde::serde_string_or_int;
+use crate::util::serde::{serde_option_string_or_int, serde_string_or_int};
 
 #[derive(Deserialize)]
 pub struct DataJson {
@@ -21,6 +21,14 @@ pub struct DataSlot {
     #[serde(deserialize_with = "serde_string_or_int")]
     pub id: u32,
     pub filename: String,
+    /// The bridge address the file is loaded to by `loadf`
+    #[serde(default, deserialize_with = "serde_option_string_or_int")]
+    pub address: Option<u32>,
+    #[serde(default, deserialize_with = "serde_option_string_or_int")]
+    pub size_maximum: Option<u32>,
+    /// The file is not loaded by APF at startup, and is instead read on demand
+    #[serde(default)]
+    pub deferload: bool,
     // We don't care about the other fields
 }
 
@@ -0,0 +1,865 @@
+use std::collections::HashMap;
+
+use crate::mem::MEMORY_SIZE;
+
+/// The maximum number of passes made to resolve forward references
+const MAX_PASSES: usize = 8;
+
+const MNEMONICS: [&str; 58] = [
+    "nop",
+    "ld",
+    "and",
+    "or",
+    "xor",
+    "add",
+    "sub",
+    "cmp",
+    "bit",
+    "ld.b",
+    "ld.w",
+    "ld.l",
+    "asl",
+    "lsr",
+    "rol",
+    "ror",
+    "printf",
+    "push",
+    "pop",
+    "gettime",
+    "loadf",
+    "seek",
+    "core",
+    "queryslot",
+    "hex.b",
+    "hex.w",
+    "hex.l",
+    "dec.b",
+    "dec.w",
+    "dec.l",
+    "ret",
+    "exit",
+    "clc",
+    "sec",
+    "close",
+    "rset",
+    "crc",
+    "mul",
+    "test",
+    "pmpw",
+    "pmpr",
+    "pmpbw",
+    "xfill",
+    "div",
+    "rfill",
+    "err",
+    "uivisible",
+    "adjfs",
+    "adjfo",
+    "adjlp",
+    "getext",
+    "getname",
+    "open",
+    "read",
+    "copy",
+    "host",
+    "jp",
+    "call",
+];
+
+///
+/// Assembles bass-style CHIP32 source into a bin file
+///
+/// Supports every instruction the disassembler decodes, labels, `constant` and `variable`
+/// definitions, `db`, `dw`, `dl`/`dd` data, `fill`, and `org`/`origin`. `architecture` and
+/// `output` lines are ignored. Errors are prefixed with the line number
+pub fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    let statements = split_statements(source);
+
+    // Forward references are resolved using the labels from the previous pass. Immediates that
+    // don't fit in 16 bits take an extra word, so repeat until the labels stop moving
+    let mut previous = HashMap::new();
+
+    for _ in 0..MAX_PASSES {
+        let mut assembler = Assembler::new(&previous, false);
+        assembler.run(&statements)?;
+
+        if !assembler.unresolved && assembler.symbols == previous {
+            break;
+        }
+
+        previous = assembler.symbols;
+    }
+
+    let mut assembler = Assembler::new(&previous, true);
+    assembler.run(&statements)?;
+
+    if assembler.symbols != previous {
+        return Err("Could not resolve label addresses".into());
+    }
+
+    Ok(assembler.output)
+}
+
+/// A labelled statement on a source line
+pub(crate) struct Statement {
+    /// The line number, starting at 1
+    pub(crate) line: usize,
+    pub(crate) labels: Vec<String>,
+    pub(crate) text: String,
+}
+
+struct Assembler<'a> {
+    /// Symbols defined so far in this pass
+    symbols: HashMap<String, u32>,
+    /// Symbols from the last pass, for forward references
+    previous: &'a HashMap<String, u32>,
+    output: Vec<u8>,
+    address: u32,
+    /// Whether unknown symbols are errors, rather than assumed to be resolved by a later pass
+    is_final: bool,
+    /// Whether an unknown symbol was used this pass
+    unresolved: bool,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(previous: &'a HashMap<String, u32>, is_final: bool) -> Self {
+        Assembler {
+            symbols: HashMap::new(),
+            previous,
+            output: Vec::new(),
+            address: 0,
+            is_final,
+            unresolved: false,
+        }
+    }
+
+    fn run(&mut self, statements: &[Statement]) -> Result<(), String> {
+        for statement in statements {
+            self.statement(statement)
+                .map_err(|err| format!("Line {}: {err}", statement.line))?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<(), String> {
+        for label in &statement.labels {
+            if self.symbols.contains_key(label) {
+                return Err(format!("Duplicate label \"{label}\""));
+            }
+
+            self.symbols.insert(label.clone(), self.address);
+        }
+
+        let text = statement.text.as_str();
+
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let (keyword, arguments) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let keyword = keyword.to_lowercase();
+        let arguments = arguments.trim();
+
+        match keyword.as_str() {
+            "architecture" | "output" => Ok(()),
+            "constant" | "variable" => {
+                let (name, value) = arguments
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected \"{keyword} name = value\""))?;
+                let name = name.trim();
+
+                if keyword == "constant" && self.symbols.contains_key(name) {
+                    return Err(format!("Duplicate constant \"{name}\""));
+                }
+
+                let value = self.evaluate(value)?;
+                self.symbols.insert(name.to_string(), value);
+
+                Ok(())
+            }
+            "org" | "origin" => {
+                self.address = self.evaluate(arguments)?;
+
+                Ok(())
+            }
+            "db" => self.data(arguments, 1),
+            "dw" => self.data(arguments, 2),
+            "dl" | "dd" => self.data(arguments, 4),
+            "fill" => {
+                let operands = split_operands(arguments);
+
+                let (length, value) = match operands.as_slice() {
+                    [length] => (self.evaluate(length)?, 0),
+                    [length, value] => (self.evaluate(length)?, self.evaluate(value)?),
+                    _ => return Err("Expected \"fill length[, value]\"".into()),
+                };
+
+                self.emit(&vec![value as u8; length as usize])
+            }
+            _ => {
+                let words = self.instruction(&keyword, arguments)?;
+                let bytes = words
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes())
+                    .collect::<Vec<u8>>();
+
+                self.emit(&bytes)
+            }
+        }
+    }
+
+    /// Emits comma separated values of `size` bytes. Strings emit one value per character
+    fn data(&mut self, arguments: &str, size: usize) -> Result<(), String> {
+        let mut bytes = Vec::new();
+
+        for operand in split_operands(arguments) {
+            let values = match operand.strip_prefix('"') {
+                Some(string) => {
+                    let string = string
+                        .strip_suffix('"')
+                        .ok_or_else(|| format!("Unterminated string {operand}"))?;
+
+                    unescape(string)?.into_iter().map(u32::from).collect()
+                }
+                None => vec![self.evaluate(&operand)?],
+            };
+
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes()[..size]);
+            }
+        }
+
+        self.emit(&bytes)
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let start = self.address as usize;
+        let end = start + bytes.len();
+
+        if end > MEMORY_SIZE {
+            return Err(format!("Address {end:#X} is past the end of memory"));
+        }
+
+        if self.output.len() < end {
+            self.output.resize(end, 0);
+        }
+
+        self.output[start..end].copy_from_slice(bytes);
+        self.address = end as u32;
+
+        Ok(())
+    }
+
+    /// Encodes an instruction as its instruction word, followed by any immediate words
+    fn instruction(&mut self, mnemonic: &str, arguments: &str) -> Result<Vec<u16>, String> {
+        let operands = split_operands(arguments)
+            .iter()
+            .map(|operand| self.operand(operand))
+            .collect::<Result<Vec<Operand>, String>>()?;
+
+        let encode = |prefix: u8, y: u8, x: u8| u16::from_be_bytes([prefix, (y << 4) | x]);
+
+        let invalid = || {
+            Err(format!(
+                "Invalid operands for {mnemonic}: \"{}\"",
+                arguments.trim()
+            ))
+        };
+
+        let words = match (mnemonic, operands.as_slice()) {
+            ("nop", []) => vec![0x0000],
+            ("ld.b" | "ld.w" | "ld.l", operands) => {
+                let base = match mnemonic {
+                    "ld.b" => 0x02,
+                    "ld.w" => 0x04,
+                    _ => 0x06,
+                };
+
+                match *operands {
+                    [Operand::Register(x), Operand::Memory(address)] => {
+                        vec![encode(base, 0, x), address]
+                    }
+                    [Operand::Memory(address), Operand::Register(x)] => {
+                        vec![encode(base + 1, 0, x), address]
+                    }
+                    [Operand::Register(x), Operand::RegisterIndirect(y)] => {
+                        vec![encode(0x30 + base, y, x)]
+                    }
+                    [Operand::RegisterIndirect(y), Operand::Register(x)] => {
+                        vec![encode(0x30 + base + 1, y, x)]
+                    }
+                    _ => return invalid(),
+                }
+            }
+            ("ld" | "and" | "or" | "xor" | "add" | "sub" | "cmp" | "bit", operands) => {
+                let index = ["ld", "and", "or", "xor", "add", "sub", "cmp", "bit"]
+                    .iter()
+                    .position(|name| *name == mnemonic)
+                    .unwrap_or(0) as u8;
+
+                match *operands {
+                    [Operand::Register(x), Operand::Immediate(value)] if value <= 0xFFFF => {
+                        vec![encode(0x08 + index, 0, x), value as u16]
+                    }
+                    [Operand::Register(x), Operand::Immediate(value)] => vec![
+                        encode(0x18 + index, 0, x),
+                        value as u16,
+                        (value >> 16) as u16,
+                    ],
+                    [Operand::Register(x), Operand::Register(y)] => {
+                        vec![encode(0x28 + index, y, x)]
+                    }
+                    _ => return invalid(),
+                }
+            }
+            ("asl" | "lsr" | "rol" | "ror", operands) => {
+                let index = ["asl", "lsr", "rol", "ror"]
+                    .iter()
+                    .position(|name| *name == mnemonic)
+                    .unwrap_or(0) as u8;
+
+                match *operands {
+                    [Operand::Register(x), Operand::Register(y)] => {
+                        vec![encode(0x20 + index, y, x)]
+                    }
+                    [Operand::Register(x), Operand::Immediate(amount)] => {
+                        if !(1..=16).contains(&amount) {
+                            return Err(format!("Shift amount {amount} must be from 1 to 16"));
+                        }
+
+                        vec![encode(0x24 + index, amount as u8 - 1, x)]
+                    }
+                    _ => return invalid(),
+                }
+            }
+            (
+                "printf" | "push" | "pop" | "gettime" | "loadf" | "seek" | "core" | "queryslot",
+                [Operand::Register(x)],
+            ) => {
+                let prefix = match mnemonic {
+                    "printf" => 0x40,
+                    "push" => 0x43,
+                    "pop" => 0x44,
+                    "gettime" => 0x49,
+                    "loadf" => 0x53,
+                    "seek" => 0x58,
+                    "core" => 0x5B,
+                    _ => 0x5D,
+                };
+
+                vec![encode(prefix, 0, *x)]
+            }
+            ("hex.b" | "hex.w" | "hex.l" | "dec.b" | "dec.w" | "dec.l", [Operand::Register(x)]) => {
+                let index = ["hex.b", "hex.w", "hex.l", "dec.b", "dec.w", "dec.l"]
+                    .iter()
+                    .position(|name| *name == mnemonic)
+                    .unwrap_or(0) as u8;
+
+                vec![encode(0x41, index, *x)]
+            }
+            ("ret", []) => vec![0x4200],
+            ("ret", [Operand::Condition(condition)]) => vec![encode(0x42, 0, *condition)],
+            ("exit", [Operand::Value(code)]) if *code <= 1 => vec![encode(0x46, 0, *code as u8)],
+            ("clc", []) => vec![0x4700],
+            ("sec", []) => vec![0x4701],
+            ("close", []) => vec![0x5700],
+            (
+                "rset" | "crc" | "mul" | "test" | "pmpw" | "pmpr" | "pmpbw" | "xfill" | "div"
+                | "rfill" | "err" | "uivisible" | "adjfs" | "adjfo" | "adjlp" | "getext"
+                | "getname" | "open" | "read" | "copy" | "host",
+                [Operand::Register(x), Operand::Register(y)],
+            ) => {
+                let prefix = match mnemonic {
+                    "rset" => 0x10,
+                    "crc" => 0x11,
+                    "mul" => 0x38,
+                    "test" => 0x39,
+                    "pmpw" => 0x3A,
+                    "pmpr" => 0x3B,
+                    "pmpbw" => 0x3C,
+                    "xfill" => 0x3D,
+                    "div" => 0x3E,
+                    "rfill" => 0x3F,
+                    "err" => 0x45,
+                    "uivisible" => 0x48,
+                    "adjfs" => 0x50,
+                    "adjfo" => 0x51,
+                    "adjlp" => 0x52,
+                    "getext" => 0x54,
+                    "getname" => 0x55,
+                    "open" => 0x56,
+                    "read" => 0x59,
+                    "copy" => 0x5A,
+                    _ => 0x5C,
+                };
+
+                vec![encode(prefix, *y, *x)]
+            }
+            ("jp" | "call", operands) => {
+                let base = if mnemonic == "jp" { 0x6 } else { 0xB };
+
+                let (condition, target) = match *operands {
+                    [Operand::Value(target)] => (0, target),
+                    [Operand::Condition(condition), Operand::Value(target)] => (condition, target),
+                    _ => return invalid(),
+                };
+
+                if target % 2 != 0 {
+                    return Err(format!("Jump target {target:#X} is not word aligned"));
+                }
+
+                // Like bass, targets past the 12 bit word address wrap
+                vec![(((base + condition) as u16) << 12) | ((target / 2) & 0xFFF) as u16]
+            }
+            _ if !MNEMONICS.contains(&mnemonic) => {
+                return Err(format!("Unknown instruction \"{mnemonic}\""))
+            }
+            _ => return invalid(),
+        };
+
+        Ok(words)
+    }
+
+    fn operand(&mut self, text: &str) -> Result<Operand, String> {
+        let text = text.trim();
+
+        if let Some(x) = register(text) {
+            return Ok(Operand::Register(x));
+        }
+
+        if let Some(condition) = ["nz", "z", "nc", "c"]
+            .iter()
+            .position(|name| text.eq_ignore_ascii_case(name))
+        {
+            return Ok(Operand::Condition(condition as u8 + 1));
+        }
+
+        if let Some(expression) = text.strip_prefix('#') {
+            return Ok(Operand::Immediate(self.evaluate(expression)?));
+        }
+
+        if let Some(inner) = text
+            .strip_prefix('(')
+            .and_then(|text| text.strip_suffix(')'))
+            .filter(|inner| is_balanced(inner))
+        {
+            if let Some(y) = register(inner) {
+                return Ok(Operand::RegisterIndirect(y));
+            }
+
+            let address = self.evaluate(inner)?;
+
+            if address as usize >= MEMORY_SIZE {
+                return Err(format!("Address {address:#X} is past the end of memory"));
+            }
+
+            return Ok(Operand::Memory(address as u16));
+        }
+
+        Ok(Operand::Value(self.evaluate(text)?))
+    }
+
+    fn evaluate(&mut self, expression: &str) -> Result<u32, String> {
+        let tokens = tokenize(expression)?;
+
+        let mut parser = ExpressionParser {
+            tokens: &tokens,
+            position: 0,
+            assembler: self,
+        };
+
+        let value = parser.expression(0)?;
+
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(format!(
+                "Unexpected \"{token}\" in \"{}\"",
+                expression.trim()
+            ));
+        }
+
+        Ok(value)
+    }
+
+    fn lookup(&mut self, name: &str) -> Result<u32, String> {
+        if let Some(value) = self.symbols.get(name).or_else(|| self.previous.get(name)) {
+            return Ok(*value);
+        }
+
+        if self.is_final {
+            return Err(format!("Unknown symbol \"{name}\""));
+        }
+
+        // Resolved by a later pass
+        self.unresolved = true;
+
+        Ok(0)
+    }
+}
+
+enum Operand {
+    Register(u8),
+    RegisterIndirect(u8),
+    Immediate(u32),
+    Memory(u16),
+    /// The index of a condition, 1 for nz through 4 for c
+    Condition(u8),
+    /// A bare value, such as a jump target or exit code
+    Value(u32),
+}
+
+/// Parses `r0` through `r15`
+fn register(text: &str) -> Option<u8> {
+    let index = text.strip_prefix(['r', 'R'])?.parse::<u8>().ok()?;
+
+    (index < 16).then_some(index)
+}
+
+/// Strips comments, and splits lines into statements separated by `;`, with their labels
+pub(crate) fn split_statements(source: &str) -> Vec<Statement> {
+    let mut statements = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let mut current = String::new();
+        let mut in_string = false;
+        let mut chars = line.chars().peekable();
+        let mut parts = Vec::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => in_string = !in_string,
+                '\\' if in_string => {
+                    current.push(c);
+
+                    if let Some(escaped) = chars.next() {
+                        current.push(escaped);
+                    }
+
+                    continue;
+                }
+                '/' if !in_string && chars.peek() == Some(&'/') => break,
+                ';' if !in_string => {
+                    parts.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+
+            current.push(c);
+        }
+
+        parts.push(current);
+
+        for part in parts {
+            let mut text = part.trim();
+            let mut labels = Vec::new();
+
+            while let Some((name, rest)) = text.split_once(':') {
+                let name = name.trim();
+
+                if !is_identifier(name) {
+                    break;
+                }
+
+                labels.push(name.to_string());
+                text = rest.trim();
+            }
+
+            if labels.is_empty() && text.is_empty() {
+                continue;
+            }
+
+            statements.push(Statement {
+                line: i + 1,
+                labels,
+                text: text.to_string(),
+            });
+        }
+    }
+
+    statements
+}
+
+/// Splits on commas outside of strings and parentheses
+pub(crate) fn split_operands(arguments: &str) -> Vec<String> {
+    let mut operands = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut depth = 0;
+
+    for c in arguments.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    operands.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        current.push(c);
+    }
+
+    if !current.trim().is_empty() || !operands.is_empty() {
+        operands.push(current.trim().to_string());
+    }
+
+    operands
+}
+
+pub(crate) fn unescape(string: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = string.chars();
+
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next() {
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('0') => '\0',
+                Some(c @ ('\\' | '"' | '\'')) => c,
+                Some(c) => return Err(format!("Unknown escape \\{c}")),
+                None => return Err("Unterminated escape".into()),
+            }
+        } else {
+            c
+        };
+
+        let mut buffer = [0; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+    }
+
+    Ok(bytes)
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Whether parentheses in `text` are balanced, so `(a)+(b)` isn't read as a memory operand
+fn is_balanced(text: &str) -> bool {
+    let mut depth = 0;
+
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return false,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth == 0
+}
+
+// Expressions
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(u32),
+    Name(String),
+    Operator(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{value:#X}"),
+            Token::Name(name) => f.write_str(name),
+            Token::Operator(operator) => f.write_str(operator),
+        }
+    }
+}
+
+/// Operators, longest first so `<<` is matched before `<`
+const OPERATORS: [&str; 13] = [
+    "<<", ">>", "+", "-", "*", "/", "%", "&", "|", "^", "~", "(", ")",
+];
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim();
+
+    while !rest.is_empty() {
+        let follows_value = matches!(
+            tokens.last(),
+            Some(Token::Number(_) | Token::Name(_) | Token::Operator(")"))
+        );
+
+        // % is modulo after a value, and otherwise a binary prefix, such as %1010
+        if let Some(binary) = rest.strip_prefix('%').filter(|_| !follows_value) {
+            let length = binary
+                .find(|c: char| c != '0' && c != '1')
+                .unwrap_or(binary.len());
+
+            let value = u32::from_str_radix(&binary[..length], 2)
+                .map_err(|_| format!("Invalid binary number in \"{}\"", expression.trim()))?;
+
+            tokens.push(Token::Number(value));
+            rest = binary[length..].trim_start();
+            continue;
+        }
+
+        if let Some(operator) = OPERATORS
+            .iter()
+            .find(|operator| rest.starts_with(*operator))
+        {
+            tokens.push(Token::Operator(operator));
+            rest = rest[operator.len()..].trim_start();
+            continue;
+        }
+
+        let length = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$'))
+            .unwrap_or(rest.len());
+
+        // Character literals, such as 'A'
+        if let Some(literal) = rest.strip_prefix('\'') {
+            let mut chars = literal.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), Some('\'')) => {
+                    tokens.push(Token::Number(c as u32));
+                    rest = chars.as_str().trim_start();
+                    continue;
+                }
+                _ => return Err(format!("Invalid character literal in \"{expression}\"")),
+            }
+        }
+
+        if length == 0 {
+            return Err(format!(
+                "Unexpected \"{rest}\" in \"{}\"",
+                expression.trim()
+            ));
+        }
+
+        let word = &rest[..length];
+
+        let number = if let Some(hex) = word
+            .strip_prefix("0x")
+            .or_else(|| word.strip_prefix("0X"))
+            .or_else(|| word.strip_prefix('$'))
+        {
+            Some(u32::from_str_radix(hex, 16))
+        } else if let Some(binary) = word.strip_prefix("0b") {
+            Some(u32::from_str_radix(binary, 2))
+        } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+            Some(word.parse())
+        } else {
+            None
+        };
+
+        tokens.push(match number {
+            Some(Ok(value)) => Token::Number(value),
+            Some(Err(_)) => return Err(format!("Invalid number \"{word}\"")),
+            None => Token::Name(word.to_string()),
+        });
+
+        rest = rest[length..].trim_start();
+    }
+
+    if tokens.is_empty() {
+        return Err("Expected a value".into());
+    }
+
+    Ok(tokens)
+}
+
+struct ExpressionParser<'a, 'b> {
+    tokens: &'a [Token],
+    position: usize,
+    assembler: &'a mut Assembler<'b>,
+}
+
+impl ExpressionParser<'_, '_> {
+    /// Binary operators by precedence, lowest first
+    const PRECEDENCE: [&'static [&'static str]; 5] = [
+        &["|"],
+        &["^"],
+        &["&"],
+        &["<<", ">>", "+", "-"],
+        &["*", "/", "%"],
+    ];
+
+    fn expression(&mut self, level: usize) -> Result<u32, String> {
+        if level == Self::PRECEDENCE.len() {
+            return self.unary();
+        }
+
+        let mut value = self.expression(level + 1)?;
+
+        while let Some(Token::Operator(operator)) = self.tokens.get(self.position) {
+            if !Self::PRECEDENCE[level].contains(operator) {
+                break;
+            }
+
+            self.position += 1;
+            let right = self.expression(level + 1)?;
+
+            value = match *operator {
+                "|" => value | right,
+                "^" => value ^ right,
+                "&" => value & right,
+                "<<" => value.checked_shl(right).unwrap_or(0),
+                ">>" => value.checked_shr(right).unwrap_or(0),
+                "+" => value.wrapping_add(right),
+                "-" => value.wrapping_sub(right),
+                "*" => value.wrapping_mul(right),
+                _ if right == 0 => return Err("Division by zero".into()),
+                "/" => value / right,
+                _ => value % right,
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<u32, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or("Expected a value")?;
+        self.position += 1;
+
+        match token {
+            Token::Number(value) => Ok(value),
+            Token::Name(name) => self.assembler.lookup(&name),
+            Token::Operator("-") => Ok(self.unary()?.wrapping_neg()),
+            Token::Operator("~") => Ok(!self.unary()?),
+            Token::Operator("(") => {
+                let value = self.expression(0)?;
+
+                match self.tokens.get(self.position) {
+                    Some(Token::Operator(")")) => {
+                        self.position += 1;
+
+                        Ok(value)
+                    }
+                    _ => Err("Expected \")\"".into()),
+                }
+            }
+            token => Err(format!("Unexpected \"{token}\"")),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt::Display;
+
+use crate::{cpu::CPU, symbols::Symbols, util::num::parse_number};
+
+/// Stops execution before the instruction at `address`, if the condition holds
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+    pub address: u16,
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    /// Whether execution should stop before the CPU's next instruction
+    pub fn is_hit(&self, cpu: &CPU) -> bool {
+        // Unconditional breakpoints are always hit
+        cpu.pc == self.address
+            && self
+                .condition
+                .iter()
+                .all(|condition| condition.evaluate(cpu))
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#06X}", self.address)?;
+
+        if let Some(condition) = &self.condition {
+            write!(f, " if {condition}")?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// An expression over the CPU state, such as `r3 == 0x20 && z`
+///
+/// Supports registers (`r0`-`r15`, `pc`, `sp`), flags (`z`, `c`), numbers, symbol names, the
+/// comparisons `==`, `!=`, `<`, `<=`, `>`, `>=`, and `&&`, `||`, `!`, and parentheses. Flags and
+/// comparisons are 1 when true, and any nonzero value is true
+#[derive(Clone, Debug)]
+pub struct Condition {
+    text: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn parse(text: &str, symbols: &Symbols) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            symbols,
+        };
+
+        let expr = parser.or()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected \"{token}\""));
+        }
+
+        Ok(Condition {
+            text: text.trim().to_string(),
+            expr,
+        })
+    }
+
+    pub fn evaluate(&self, cpu: &CPU) -> bool {
+        self.expr.evaluate(cpu) != 0
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Operand {
+    Register(usize),
+    Pc,
+    Sp,
+    Zero,
+    Carry,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Value(u32),
+    Operand(Operand),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, cpu: &CPU) -> u32 {
+        match self {
+            Expr::Value(value) => *value,
+            Expr::Operand(operand) => match operand {
+                Operand::Register(index) => cpu.work_regs[*index],
+                Operand::Pc => cpu.pc as u32,
+                Operand::Sp => cpu.sp as u32,
+                Operand::Zero => cpu.zero as u32,
+                Operand::Carry => cpu.carry as u32,
+            },
+            Expr::Not(expr) => (expr.evaluate(cpu) == 0) as u32,
+            Expr::Binary(op, left, right) => {
+                let left = left.evaluate(cpu);
+
+                // Short circuit
+                match op {
+                    BinaryOp::And if left == 0 => return 0,
+                    BinaryOp::Or if left != 0 => return 1,
+                    _ => {}
+                }
+
+                let right = right.evaluate(cpu);
+
+                (match op {
+                    BinaryOp::Eq => left == right,
+                    BinaryOp::Ne => left != right,
+                    BinaryOp::Lt => left < right,
+                    BinaryOp::Le => left <= right,
+                    BinaryOp::Gt => left > right,
+                    BinaryOp::Ge => left >= right,
+                    BinaryOp::And | BinaryOp::Or => right != 0,
+                }) as u32
+            }
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if char.is_whitespace() {
+            continue;
+        }
+
+        if char.is_ascii_alphanumeric() || char == '_' || char == '.' {
+            let mut token = char.to_string();
+
+            while let Some(&next) = chars.peek() {
+                if !(next.is_ascii_alphanumeric() || next == '_' || next == '.') {
+                    break;
+                }
+
+                token.push(next);
+                chars.next();
+            }
+
+            tokens.push(token);
+            continue;
+        }
+
+        let token = match (char, chars.peek()) {
+            ('=', Some('=')) | ('!', Some('=')) | ('<', Some('=')) | ('>', Some('=')) => {
+                let token = format!("{char}=");
+                chars.next();
+                token
+            }
+            ('&', Some('&')) | ('|', Some('|')) => {
+                let token = format!("{char}{char}");
+                chars.next();
+                token
+            }
+            ('!' | '<' | '>' | '(' | ')', _) => char.to_string(),
+            _ => return Err(format!("Unexpected character '{char}'")),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+    symbols: &'a Symbols,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.position).map(String::as_str);
+        self.position += 1;
+
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+
+        while self.peek() == Some("||") {
+            self.next();
+            expr = Expr::Binary(BinaryOp::Or, Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.comparison()?;
+
+        while self.peek() == Some("&&") {
+            self.next();
+            expr = Expr::Binary(BinaryOp::And, Box::new(expr), Box::new(self.comparison()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+
+        let op = match self.peek() {
+            Some("==") => BinaryOp::Eq,
+            Some("!=") => BinaryOp::Ne,
+            Some("<") => BinaryOp::Lt,
+            Some("<=") => BinaryOp::Le,
+            Some(">") => BinaryOp::Gt,
+            Some(">=") => BinaryOp::Ge,
+            _ => return Ok(left),
+        };
+
+        self.next();
+
+        Ok(Expr::Binary(op, Box::new(left), Box::new(self.unary()?)))
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some("!") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let symbols = self.symbols;
+
+        let Some(token) = self.next() else {
+            return Err("Unexpected end of condition".into());
+        };
+
+        if token == "(" {
+            let expr = self.or()?;
+
+            return match self.next() {
+                Some(")") => Ok(expr),
+                _ => Err("Expected \")\"".into()),
+            };
+        }
+
+        let lowercase = token.to_ascii_lowercase();
+
+        let operand = match lowercase.as_str() {
+            "pc" => Some(Operand::Pc),
+            "sp" => Some(Operand::Sp),
+            "z" => Some(Operand::Zero),
+            "c" => Some(Operand::Carry),
+            register => register
+                .strip_prefix('r')
+                .and_then(|index| index.parse::<usize>().ok())
+                .filter(|index| *index < 16)
+                .map(Operand::Register),
+        };
+
+        if let Some(operand) = operand {
+            return Ok(Expr::Operand(operand));
+        }
+
+        if let Ok(value) = parse_number(token) {
+            return Ok(Expr::Value(value));
+        }
+
+        symbols
+            .lookup(token)
+            .map(Expr::Value)
+            .ok_or_else(|| format!("Unknown name \"{token}\""))
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::HashMap, rc::Rc};
+
+const PAGE_SIZE: usize = 4 * 1024;
+
+///
+/// A sparse model of the 32-bit address space on the other side of the APF bridge
+///
+/// Pages are shared between clones until one is written to, so cloning the CPU stays cheap
+/// even after loading large files. Unwritten addresses read as 0
+#[derive(Clone, Default)]
+pub struct BridgeMemory {
+    pages: HashMap<u32, Rc<[u8; PAGE_SIZE]>>,
+}
+
+impl BridgeMemory {
+    pub fn read_byte(&self, address: u32) -> u8 {
+        let (page, offset) = split_address(address);
+
+        self.pages.get(&page).map_or(0, |page| page[offset])
+    }
+
+    pub fn write_byte(&mut self, address: u32, byte: u8) {
+        let (page, offset) = split_address(address);
+
+        self.page_mut(page)[offset] = byte;
+    }
+
+    /// Reads a 32-bit value. The bridge is big endian
+    pub fn read_long(&self, address: u32) -> u32 {
+        let mut bytes = [0; 4];
+
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = self.read_byte(address.wrapping_add(i as u32));
+        }
+
+        u32::from_be_bytes(bytes)
+    }
+
+    /// Writes a 32-bit value. The bridge is big endian
+    pub fn write_long(&mut self, address: u32, value: u32) {
+        self.write_bytes(address, &value.to_be_bytes());
+    }
+
+    pub fn read_bytes(&self, address: u32, length: usize) -> Vec<u8> {
+        (0..length)
+            .map(|i| self.read_byte(address.wrapping_add(i as u32)))
+            .collect()
+    }
+
+    pub fn write_bytes(&mut self, address: u32, bytes: &[u8]) {
+        let mut bytes = bytes.iter();
+
+        self.fill_with(address, bytes.len() as u32, || {
+            *bytes.next().expect("Ran out of bytes to write")
+        });
+    }
+
+    /// Fills `length` bytes starting at `address` with `byte`
+    pub fn fill(&mut self, address: u32, length: u32, byte: u8) {
+        self.fill_with(address, length, || byte);
+    }
+
+    /// Fills `length` bytes starting at `address` with the output of `generator`, called once per byte in address order
+    pub fn fill_with<F: FnMut() -> u8>(&mut self, address: u32, length: u32, mut generator: F) {
+        let mut address = address;
+        let mut remaining = length as usize;
+
+        while remaining > 0 {
+            let (page, offset) = split_address(address);
+            let count = remaining.min(PAGE_SIZE - offset);
+
+            self.page_mut(page)[offset..offset + count]
+                .iter_mut()
+                .for_each(|byte| *byte = generator());
+
+            address = address.wrapping_add(count as u32);
+            remaining -= count;
+        }
+    }
+
+    /// The number of bytes that have backing storage, in page sized increments
+    pub fn allocated_size(&self) -> usize {
+        self.pages.len() * PAGE_SIZE
+    }
+
+    fn page_mut(&mut self, page: u32) -> &mut [u8; PAGE_SIZE] {
+        let page = self
+            .pages
+            .entry(page)
+            .or_insert_with(|| Rc::new([0; PAGE_SIZE]));
+
+        Rc::make_mut(page)
+    }
+}
+
+fn split_address(address: u32) -> (u32, usize) {
+    (
+        address / PAGE_SIZE as u32,
+        (address % PAGE_SIZE as u32) as usize,
+    )
+}
@@ -0,0 +1,41 @@
+use std::{str::FromStr, time::Instant};
+
+/// The estimated number of CPU cycles executed per millisecond on hardware
+pub const CYCLES_PER_MILLISECOND: u64 = 1000;
+
+#[derive(Clone, Debug, Default)]
+pub enum ClockMode {
+    /// Time advances with the executed cycle count, so runs are repeatable
+    #[default]
+    Virtual,
+    /// Time never advances, always reporting this many milliseconds
+    Fixed(u32),
+    /// Time follows the host's wall clock, starting from this instant
+    Host(Instant),
+}
+
+impl ClockMode {
+    /// The current time in milliseconds, as reported by `gettime`
+    pub fn milliseconds(&self, cycles: u64) -> u32 {
+        match self {
+            ClockMode::Virtual => (cycles / CYCLES_PER_MILLISECOND) as u32,
+            ClockMode::Fixed(milliseconds) => *milliseconds,
+            ClockMode::Host(start) => start.elapsed().as_millis() as u32,
+        }
+    }
+}
+
+impl FromStr for ClockMode {
+    type Err = String;
+
+    /// Parses `virtual`, `host`, or a fixed number of milliseconds
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "virtual" => Ok(ClockMode::Virtual),
+            "host" => Ok(ClockMode::Host(Instant::now())),
+            fixed => fixed.parse().map(ClockMode::Fixed).map_err(|_| {
+                format!("Expected \"virtual\", \"host\", or a number of milliseconds. Found \"{fixed}\"")
+            }),
+        }
+    }
+}
@@ -1,23 +1,36 @@
 use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
     ffi::OsStr,
     fmt::Display,
-    fs::File,
+    fs::{self, File},
     io::{self, Read},
-    ops::{Shl, Shr},
+    ops::{Range, Shl, Shr},
     path::Path,
+    rc::Rc,
 };
 
 use serde::Serialize;
 
 use crate::{
     apf::DataSlot,
-    mem::Memory,
+    bridge::BridgeMemory,
+    clock::ClockMode,
+    disasm::decode,
+    host::{command_name, DefaultHostHandler, HostHandler},
+    mem::{BoundsPolicy, Memory, MemoryEvent, RomWritePolicy, WatchpointHit, MEMORY_SIZE},
+    timing::{instruction_cycles, transfer_cycles},
     util::{
         bitwise::BitIndex,
+        crc::crc32,
         num::{LowerLong, LowerWord},
+        rng::Rng,
     },
 };
 
+/// The number of stack entries available by default
+pub const DEFAULT_STACK_DEPTH: usize = 31;
+
 #[derive(Clone)]
 pub struct CPU {
     pub pc: u16,
@@ -31,30 +44,156 @@ pub struct CPU {
     pub zero: bool,
 
     pub ram: Memory,
-    // TODO: It is unclear if this should live in memory or separately, and unclear how large it should be
-    pub stack: [u32; 32],
+    /// Memory on the FPGA side of the bridge
+    pub bridge: BridgeMemory,
+    /// The source of random data for `rfill`
+    pub rng: Rng,
+    /// The call and data stack. Its length is the maximum number of entries, and can be changed
+    /// before execution to simulate a different stack depth
+    pub stack: Vec<u32>,
 
     pub file_state: FileState,
+    /// The visibility of interact elements, as set by `uivisible`
+    pub ui: UiState,
 
     pub halt: HaltState,
 
+    /// The number of cycles executed so far, as estimated by the timing model
+    pub cycles: u64,
+    /// The source of time for `gettime`
+    pub clock: ClockMode,
+
+    /// The last instruction executed, formatted by the disassembler
     pub formatted_instruction: String,
     pub logs: Vec<String>,
     pub active_bitstream: Option<usize>,
+    /// Receives `host` commands. Shared between clones of this CPU
+    pub host: Rc<RefCell<dyn HostHandler>>,
+    /// When set, `host` commands are skipped instead of sent to `host`, so a clone can step ahead
+    /// without side effects
+    pub speculative: bool,
+    /// Whether the last step skipped a `host` command because the CPU was speculative. The step
+    /// must be repeated without `speculative` to perform it
+    pub host_skipped: bool,
 }
 
 #[derive(Clone)]
 pub struct FileState {
     pub slots: Vec<DataSlot>,
+    /// Adjustments made by `adjfs`, `adjfo`, and `adjlp`, keyed by slot ID
+    pub adjustments: HashMap<u32, SlotAdjustment>,
 
     pub loaded: FileLoadedState,
 }
 
-#[derive(Clone)]
+impl FileState {
+    /// The bridge address `slot` is loaded to, honoring any `adjlp`
+    pub fn load_address(&self, slot: &DataSlot) -> Option<u32> {
+        self.adjustments
+            .get(&slot.id)
+            .and_then(|adjustment| adjustment.load_pointer)
+            .or(slot.address)
+    }
+
+    /// The contents of `slot` that are loaded into the bridge, honoring any `adjfo` and `adjfs`
+    pub fn load_data(&self, slot: &DataSlot) -> Result<Vec<u8>, String> {
+        let mut data = file_to_buffer(&slot.filename).map_err(|err| err.to_string())?;
+        let range = self.adjusted_range(slot, data.len())?;
+
+        data.truncate(range.end);
+        data.drain(..range.start);
+
+        Ok(data)
+    }
+
+    /// The length of the contents of `slot` that are loaded, without reading the file
+    pub fn data_length(&self, slot: &DataSlot) -> Result<usize, String> {
+        let metadata = fs::metadata(&slot.filename).map_err(|err| err.to_string())?;
+
+        if !metadata.is_file() {
+            return Err(format!("{} is not a file", slot.filename));
+        }
+
+        self.adjusted_range(slot, metadata.len() as usize)
+            .map(|range| range.len())
+    }
+
+    /// The range of a file of `length` bytes that is loaded for `slot`, honoring any `adjfo` and
+    /// `adjfs`
+    fn adjusted_range(&self, slot: &DataSlot, length: usize) -> Result<Range<usize>, String> {
+        let Some(adjustment) = self.adjustments.get(&slot.id) else {
+            return Ok(0..length);
+        };
+
+        let start = adjustment.offset.map_or(0, |offset| offset as usize);
+
+        if start > length {
+            return Err(format!(
+                "Offset {start:#X} is past the end of the file ({length:#X})"
+            ));
+        }
+
+        // The size can only shrink the remaining data
+        let end = adjustment
+            .size
+            .map_or(length, |size| length.min(start + size as usize));
+
+        Ok(start..end)
+    }
+}
+
+/// Overrides of a slot's file parameters
+#[derive(Clone, Default, Serialize)]
+pub struct SlotAdjustment {
+    /// The number of bytes to load
+    pub size: Option<u32>,
+    /// The offset into the file to start loading from
+    pub offset: Option<u32>,
+    /// The bridge address to load to
+    pub load_pointer: Option<u32>,
+}
+
+#[derive(Clone, Default, Serialize)]
+pub struct UiState {
+    /// Whether each interact element is visible, keyed by element ID. Elements never
+    /// set by `uivisible` are absent
+    pub visibility: BTreeMap<u32, bool>,
+}
+
+/// The outcome of a call to `CPU::step`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepResult {
+    Running,
+    /// The CPU has halted, either during this step or before it
+    Halted,
+    /// The instruction accessed a watched address. Execution can be continued
+    Watchpoint(WatchpointHit),
+}
+
+#[derive(Clone, Serialize)]
 pub enum HaltState {
     Running,
     Success,
     Failure,
+    /// The CPU encountered an instruction it could not execute
+    Fault {
+        /// The address of the faulting instruction
+        pc: u16,
+        /// The faulting instruction word
+        opcode: u16,
+        reason: String,
+    },
+}
+
+impl Display for HaltState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HaltState::Running => "Running",
+            HaltState::Success => "Success",
+            HaltState::Failure => "Failure",
+            HaltState::Fault { .. } => "Fault",
+        })
+    }
 }
 
 #[derive(Clone, Serialize)]
@@ -62,84 +201,88 @@ pub enum FileLoadedState {
     None,
     Loaded {
         slot: u32,
-        data: Vec<u8>,
+        /// The file contents, shared between clones of the CPU
+        data: Rc<[u8]>,
         offset: usize,
     },
 }
 
+#[derive(Clone, Copy)]
 enum DataSize {
     Byte,
     Word,
     Long,
 }
 
-impl Display for DataSize {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            DataSize::Byte => ".b",
-            DataSize::Word => ".w",
-            DataSize::Long => ".l",
-        })
-    }
-}
-
-enum InstructionKind {
-    SingleReg {
-        x: u8,
-        size: Option<DataSize>,
-    },
-    DoubleReg {
-        x: u8,
-        y: u8,
-        /// If Some, the direction a memory access flows
-        ///
-        /// If None, no memory access
-        mem_direction_into_reg: Option<bool>,
-        size: Option<DataSize>,
-    },
-    Immediate {
-        x: u8,
-        n: u32,
-        size: Option<DataSize>,
-    },
-    RegMem {
-        x: u8,
-        address: u16,
-        direction_into_reg: bool,
-        size: DataSize,
-    },
-    Jump {
-        immediate: Option<u16>,
-        always: bool,
-        zero: Option<bool>,
-        carry: Option<bool>,
-    },
-    None,
-}
-
 impl CPU {
-    pub fn step(&mut self) {
+    pub fn step(&mut self) -> StepResult {
         if match self.halt {
             HaltState::Running => false,
             _ => true,
         } {
             // Not running, do nothing
-            return;
+            return StepResult::Halted;
+        }
+
+        // Discard accesses made outside of a step, such as by a debugger, so they aren't reported
+        self.ram.take_events();
+        self.host_skipped = false;
+
+        let inst_pc = self.pc;
+        let Some(inst_word) = self.pc_word() else {
+            // The fetch's out of bounds event is handled here, before anything is decoded
+            self.ram.take_events();
+
+            return self.overran_memory();
+        };
+
+        self.execute(inst_pc, inst_word);
+
+        let mut watchpoint = None;
+
+        for event in self.ram.take_events() {
+            if let Some(hit) = self.memory_event(inst_pc, inst_word, event) {
+                // Report the first watchpoint
+                watchpoint.get_or_insert(hit);
+            }
         }
 
-        if self.pc >= 0x2000 {
-            // We ran off of the end of memory. Halt
-            self.halt = HaltState::Failure;
+        match (&self.halt, watchpoint) {
+            (HaltState::Running, Some(hit)) => StepResult::Watchpoint(hit),
+            (HaltState::Running, None) => StepResult::Running,
+            _ => StepResult::Halted,
+        }
+    }
 
-            self.logs.push(format!("ERROR: Overran memory. Halting"));
-            return;
+    ///
+    /// Applies the memory bounds policy to the PC running off of the end of memory
+    ///
+    fn overran_memory(&mut self) -> StepResult {
+        if self.ram.bounds_policy == BoundsPolicy::ErrorJump {
+            self.logs.push("Sim: Overran memory".into());
+            self.jump_to_error();
+
+            return StepResult::Running;
         }
 
-        self.formatted_instruction = String::new();
+        // We ran off of the end of memory. Halt
+        self.halt = HaltState::Failure;
+
+        self.logs.push("ERROR: Overran memory. Halting".into());
+        StepResult::Halted
+    }
+
+    ///
+    /// Executes the instruction `inst_word`, fetched from `inst_pc`. An instruction that makes an out
+    /// of bounds access that isn't wrapped is abandoned at that access, without further side effects
+    ///
+    fn execute(&mut self, inst_pc: u16, inst_word: u16) {
+        self.formatted_instruction = decode(self.ram.bytes(), inst_pc).to_string();
 
-        let inst_word = self.pc_word();
         let [inst_prefix_byte, inst_suffix_byte] = inst_word.to_be_bytes();
 
+        self.cycles += instruction_cycles(inst_prefix_byte);
+
         let inst_prefix_upper_nibble = (inst_prefix_byte >> 4) & 0xF;
         let reg_x_index = inst_suffix_byte & 0xF;
         let reg_y_index = (inst_suffix_byte >> 4) & 0xF;
@@ -151,7 +294,6 @@ impl CPU {
         match inst_prefix_byte {
             0x0 => {
                 // nop
-                self.formatted_instruction = format!("nop")
             }
             0x02 | 0x32 => {
                 // ld.b Rx,(nnnn) | ld.b Rx,(Ry)
@@ -180,7 +322,6 @@ impl CPU {
             0x08 | 0x18 | 0x28 => {
                 // ld Rx,#16/32 | ld Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "ld",
                     inst_suffix_byte,
                     false,
                     alu_reg_bit,
@@ -192,7 +333,6 @@ impl CPU {
             0x09 | 0x19 | 0x29 => {
                 // and Rx,#16/32 | and Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "and",
                     inst_suffix_byte,
                     false,
                     alu_reg_bit,
@@ -204,7 +344,6 @@ impl CPU {
             0x0A | 0x1A | 0x2A => {
                 // or Rx,#16/32 | or Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "or",
                     inst_suffix_byte,
                     false,
                     alu_reg_bit,
@@ -216,7 +355,6 @@ impl CPU {
             0x0B | 0x1B | 0x2B => {
                 // xor Rx,#16/32 | xor Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "xor",
                     inst_suffix_byte,
                     false,
                     alu_reg_bit,
@@ -228,7 +366,6 @@ impl CPU {
             0x0C | 0x1C | 0x2C => {
                 // add Rx,#16/32 | add Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "add",
                     inst_suffix_byte,
                     true,
                     alu_reg_bit,
@@ -240,7 +377,6 @@ impl CPU {
             0x0D | 0x1D | 0x2D => {
                 // sub Rx,#16/32 | sub Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "sub",
                     inst_suffix_byte,
                     true,
                     alu_reg_bit,
@@ -252,7 +388,6 @@ impl CPU {
             0x0E | 0x1E | 0x2E => {
                 // cmp Rx,#16/32 | cmp Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "cmp",
                     inst_suffix_byte,
                     true,
                     alu_reg_bit,
@@ -264,7 +399,6 @@ impl CPU {
             0x0F | 0x1F | 0x2F => {
                 // bit Rx,#16/32 | bit Rx,Ry
                 self.alu_immediate_or_reg_inst(
-                    "bit",
                     inst_suffix_byte,
                     false,
                     alu_reg_bit,
@@ -273,11 +407,46 @@ impl CPU {
                     |reg, immediate| (reg & immediate, false),
                 )
             }
-            0x10 => todo!("RSET"),
-            0x11 => todo!("CRC"),
+            0x10 => {
+                // rset Rx,Ry
+                self.alu_double_value_inst(inst_suffix_byte, true, false, |reg_x, reg_y| {
+                    // Only the lower 5 bits select the bit to clear
+                    let index = reg_y & 0x1F;
+
+                    // Carry is the value of the bit before it was cleared
+                    (reg_x & !(1 << index), reg_x.bit_at_index(index))
+                })
+            }
+            0x11 => {
+                // crc Rx,Ry
+                let reg_x = self.get_reg(reg_x_index);
+                let reg_y = self.get_reg(reg_y_index);
+
+                if reg_x as u64 + reg_y as u64 > 0x2000 {
+                    // Range extends past the end of memory
+                    self.logs.push("Sim: crc overran end of memory".into());
+
+                    return self.jump_to_error();
+                }
+
+                let bytes: Vec<u8> = (reg_x..reg_x + reg_y)
+                    .map(|address| self.ram.read_byte(address.to_lower_word()))
+                    .collect();
+
+                let crc = crc32(&bytes);
+
+                self.cycles += transfer_cycles(reg_y as u64);
+
+                self.set_reg(reg_x_index, crc);
+                self.set_zero(crc);
+
+                self.logs.push(format!(
+                    "Sim: CRC32 of {reg_y:#X} bytes at {reg_x:#X} is {crc:#010X}"
+                ));
+            }
             0x20 => {
                 // asl Rx,Ry
-                self.alu_double_value_inst("asl", inst_suffix_byte, true, false, |reg_x, reg_y| {
+                self.alu_double_value_inst(inst_suffix_byte, true, false, |reg_x, reg_y| {
                     // u64 is used to capture if there is carry
                     let long_long = (reg_x as u64).shl(reg_y);
                     (long_long.to_lower_long(), long_long > u32::MAX as u64)
@@ -285,7 +454,7 @@ impl CPU {
             }
             0x21 => {
                 // lsr Rx,Ry
-                self.alu_double_value_inst("lsr", inst_suffix_byte, true, false, |reg_x, reg_y| {
+                self.alu_double_value_inst(inst_suffix_byte, true, false, |reg_x, reg_y| {
                     let long = reg_x.shr(reg_y);
 
                     // Carry is bit at position reg_y - 1
@@ -295,7 +464,7 @@ impl CPU {
             }
             0x22 => {
                 // rol Rx,Ry
-                self.alu_double_value_inst("rol", inst_suffix_byte, true, false, |reg_x, reg_y| {
+                self.alu_double_value_inst(inst_suffix_byte, true, false, |reg_x, reg_y| {
                     let long = reg_x.shl(reg_y);
 
                     // Carry is bit at 32 - reg_y
@@ -305,7 +474,7 @@ impl CPU {
             }
             0x23 => {
                 // ror Rx,Ry
-                self.alu_double_value_inst("ror", inst_suffix_byte, true, false, |reg_x, reg_y| {
+                self.alu_double_value_inst(inst_suffix_byte, true, false, |reg_x, reg_y| {
                     let long = reg_x.shr(reg_y);
 
                     // Carry is bit at reg_y - 1
@@ -315,7 +484,7 @@ impl CPU {
             }
             0x24 => {
                 // asl Rx,#
-                self.alu_double_value_inst("asl", inst_suffix_byte, true, true, |reg, immediate| {
+                self.alu_double_value_inst(inst_suffix_byte, true, true, |reg, immediate| {
                     // For some reason the immediate is locked to be >= 1
                     let immediate = immediate + 1;
 
@@ -325,7 +494,7 @@ impl CPU {
             }
             0x25 => {
                 // lsr Rx,#
-                self.alu_double_value_inst("lsr", inst_suffix_byte, true, true, |reg, immediate| {
+                self.alu_double_value_inst(inst_suffix_byte, true, true, |reg, immediate| {
                     // For some reason the immediate is locked to be >= 1
                     let immediate = immediate + 1;
                     let long = reg.shr(immediate);
@@ -337,7 +506,7 @@ impl CPU {
             }
             0x26 => {
                 // rol Rx,#
-                self.alu_double_value_inst("rol", inst_suffix_byte, true, true, |reg, immediate| {
+                self.alu_double_value_inst(inst_suffix_byte, true, true, |reg, immediate| {
                     // For some reason the immediate is locked to be >= 1
                     let immediate = immediate + 1;
                     let long = reg.shl(immediate);
@@ -349,7 +518,7 @@ impl CPU {
             }
             0x27 => {
                 // ror Rx,#
-                self.alu_double_value_inst("ror", inst_suffix_byte, true, true, |reg, immediate| {
+                self.alu_double_value_inst(inst_suffix_byte, true, true, |reg, immediate| {
                     // For some reason the immediate is locked to be >= 1
                     let immediate = immediate + 1;
                     let long = reg.shr(immediate);
@@ -361,22 +530,12 @@ impl CPU {
             }
             0x38 => {
                 // mul Rx,Ry
-                self.alu_double_value_inst("mul", inst_suffix_byte, false, false, |reg_x, reg_y| {
+                self.alu_double_value_inst(inst_suffix_byte, false, false, |reg_x, reg_y| {
                     reg_x.overflowing_mul(reg_y)
                 })
             }
             0x39 => {
                 // test Rx,Ry
-                self.set_instruction_string(
-                    "test",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                );
-
                 let reg_x = (self.get_reg(reg_x_index) & 0x1FFF) as u16;
                 let reg_y = (self.get_reg(reg_y_index) & 0x1FFF) as u16;
 
@@ -422,66 +581,86 @@ impl CPU {
                     y_value = self.ram.read_byte(y_address);
                 }
             }
-            0x3A..=0x3D | 0x3F => {
-                // pmpw Rx,Ry | pmpr Rx,Ry | pmpbw Rx,Ry | xfill Rx,Ry | rfill Rx,Ry
-                // Unimplemented
+            0x3A..=0x3C => {
+                // pmpw Rx,Ry | pmpr Rx,Ry | pmpbw Rx,Ry
                 let reg_x = self.get_reg(reg_x_index);
                 let reg_y = self.get_reg(reg_y_index);
 
-                let name = match inst_prefix_byte {
+                match inst_prefix_byte {
                     0x3A => {
+                        self.bridge.write_long(reg_x, reg_y);
+
                         self.logs.push(format!(
                             "Sim: pmpw write {reg_y:#X} to FPGA memory at {reg_x:#X}"
                         ));
-
-                        "pmpw"
                     }
                     0x3B => {
-                        self.logs
-                            .push(format!("Sim: pmpr read from FPGA memory at {reg_x:#X}"));
+                        let value = self.bridge.read_long(reg_x);
 
-                        "pmpr"
+                        self.set_reg(reg_y_index, value);
+
+                        self.logs.push(format!(
+                            "Sim: pmpr read {value:#X} from FPGA memory at {reg_x:#X}"
+                        ));
                     }
                     0x3C => {
+                        // Byte swapped write
+                        self.bridge.write_long(reg_x, reg_y.swap_bytes());
+
                         self.logs.push(format!(
                             "Sim: pmpbw write bytes {reg_y:#X} to FPGA memory at {reg_x:#X}"
                         ));
-
-                        "pmpbw"
                     }
-                    0x3D => {
-                        let length = reg_y & 0xFFFFFF;
-                        let fill = (reg_y & 0xFF000000) >> 24;
+                    _ => unreachable!(),
+                }
+            }
+            0x3D | 0x3F => {
+                // xfill Rx,Ry | rfill Rx,Ry
+                let name = if inst_prefix_byte == 0x3D {
+                    "xfill"
+                } else {
+                    "rfill"
+                };
 
-                        let last_address = reg_x + length;
+                let reg_x = self.get_reg(reg_x_index);
+                let reg_y = self.get_reg(reg_y_index);
 
-                        self.logs.push(format!(
-                            "Sim: xfill filled bytes from {reg_x:#X} to {last_address:#X} (length {length:#X}), filling with {fill:#X}"
-                        ));
+                // xfill packs the length into the lower 24 bits, which rfill is held to as well
+                let length = if inst_prefix_byte == 0x3D {
+                    reg_y & 0xFFFFFF
+                } else {
+                    reg_y
+                };
 
-                        "xfill"
-                    }
-                    0x3F => {
-                        let last_address = reg_x + reg_y;
+                if length > 0xFFFFFF || reg_x as u64 + length as u64 > 1 << 32 {
+                    // Range is too long, or extends past the end of the bridge
+                    self.logs.push(format!(
+                        "Sim: {name} of length {length:#X} from {reg_x:#X} is out of range"
+                    ));
 
-                        self.logs.push(format!(
-                            "Sim: rfill filled bytes from {reg_x:#X} to {last_address:#X} (length {reg_y:#X}), filling with random data"
-                        ));
+                    return self.jump_to_error();
+                }
 
-                        "rfill"
-                    }
-                    _ => unreachable!(),
-                };
+                self.cycles += transfer_cycles(length as u64);
 
-                self.set_instruction_string(
-                    name,
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                );
+                let last_address = reg_x.wrapping_add(length);
+
+                if inst_prefix_byte == 0x3D {
+                    let fill = ((reg_y & 0xFF000000) >> 24) as u8;
+
+                    self.bridge.fill(reg_x, length, fill);
+
+                    self.logs.push(format!(
+                        "Sim: xfill filled bytes from {reg_x:#X} to {last_address:#X} (length {length:#X}), filling with {fill:#X}"
+                    ));
+                } else {
+                    let rng = &mut self.rng;
+                    self.bridge.fill_with(reg_x, length, || rng.next_byte());
+
+                    self.logs.push(format!(
+                        "Sim: rfill filled bytes from {reg_x:#X} to {last_address:#X} (length {length:#X}), filling with random data"
+                    ));
+                }
             }
             0x3E => {
                 // div Rx,Ry
@@ -503,16 +682,6 @@ impl CPU {
                     self.set_zero(quotient);
                     self.set_carry(remainder == 0);
                 }
-
-                self.set_instruction_string(
-                    "div",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
             }
             0x40 => {
                 // printf Rx
@@ -521,12 +690,19 @@ impl CPU {
                 let mut string_bytes = Vec::new();
 
                 let mut count = 1;
-                let mut byte = self.ram.read_byte(address);
+                let Some(mut byte) = self.ram.checked_read_byte(address) else {
+                    return;
+                };
                 // Max at 255 chars, and stop at nullchar
                 while count < 256 && byte != 0 {
                     string_bytes.push(byte);
 
-                    byte = self.ram.read_byte(address + count);
+                    let Some(next_byte) = self.ram.checked_read_byte(address.wrapping_add(count))
+                    else {
+                        return;
+                    };
+
+                    byte = next_byte;
                     count += 1;
                 }
 
@@ -538,14 +714,6 @@ impl CPU {
                 );
 
                 self.logs.push(string);
-
-                self.set_instruction_string(
-                    "printf",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                );
             }
             0x41 => {
                 // hex.* Rx | dec.* Rx
@@ -553,28 +721,34 @@ impl CPU {
 
                 let reg_x = self.get_reg(reg_x_index);
 
-                let (string, size) = match identifier {
+                let string = match identifier {
                     // hex
                     0 => {
                         let byte = reg_x.to_le_bytes()[0];
-                        (format!("{byte:02X}"), DataSize::Byte)
+                        format!("{byte:02X}")
                     }
                     1 => {
                         let word = reg_x.to_lower_word();
-                        (format!("{word:04X}"), DataSize::Word)
+                        format!("{word:04X}")
                     }
-                    2 => (format!("{reg_x:08X}"), DataSize::Long),
+                    2 => format!("{reg_x:08X}"),
                     // dec
                     3 => {
                         let byte = reg_x.to_le_bytes()[0];
-                        (format!("{byte:02}"), DataSize::Byte)
+                        format!("{byte:02}")
                     }
                     4 => {
                         let word = reg_x.to_lower_word();
-                        (format!("{word:04}"), DataSize::Word)
+                        format!("{word:04}")
+                    }
+                    5 => format!("{reg_x:08}"),
+                    _ => {
+                        return self.fault(
+                            inst_pc,
+                            inst_word,
+                            format!("Unexpected identifier {identifier} in 0x41"),
+                        )
                     }
-                    5 => (format!("{reg_x:08}"), DataSize::Long),
-                    _ => panic!("Unexpected identifier {identifier} in 0x41"),
                 };
 
                 let string = if identifier < 3 {
@@ -584,91 +758,36 @@ impl CPU {
                 };
 
                 self.logs.push(string);
-
-                self.set_instruction_string(
-                    if identifier < 3 { "hex" } else { "dec" },
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: Some(size),
-                    },
-                );
             }
             0x42 => {
                 // ret *
                 let identifier = reg_x_index;
 
+                if identifier > 4 {
+                    return self.fault(
+                        inst_pc,
+                        inst_word,
+                        format!("Unexpected identifier {identifier} in 0x42"),
+                    );
+                }
+
                 self.return_inst(|zero, carry| match identifier {
                     0 => true,   // ret
                     1 => !zero,  // ret NZ
                     2 => zero,   // ret Z
                     3 => !carry, // ret NC
                     4 => carry,  // ret C
-                    _ => panic!("Unexpected identifier {identifier} in 0x42"),
+                    _ => unreachable!(),
                 });
-
-                self.set_instruction_string(
-                    "ret",
-                    match identifier {
-                        0 => InstructionKind::Jump {
-                            immediate: None,
-                            always: true,
-                            zero: None,
-                            carry: None,
-                        },
-                        1 => InstructionKind::Jump {
-                            immediate: None,
-                            always: false,
-                            zero: Some(false),
-                            carry: None,
-                        },
-                        2 => InstructionKind::Jump {
-                            immediate: None,
-                            always: false,
-                            zero: Some(true),
-                            carry: None,
-                        },
-                        3 => InstructionKind::Jump {
-                            immediate: None,
-                            always: false,
-                            zero: None,
-                            carry: Some(false),
-                        },
-                        4 => InstructionKind::Jump {
-                            immediate: None,
-                            always: false,
-                            zero: None,
-                            carry: Some(true),
-                        },
-                        _ => unreachable!(),
-                    },
-                )
             }
             0x43 => {
                 // push Rx
-                let reg_x_index = reg_x_index;
-
-                self.stack[self.sp] = self.get_reg(reg_x_index);
-
-                self.sp += 1;
-
-                self.set_instruction_string(
-                    "push",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                )
+                if !self.push_stack(self.get_reg(reg_x_index)) {
+                    self.jump_to_error();
+                }
             }
             0x44 => {
                 // pop Rx
-                self.set_instruction_string(
-                    "pop",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                );
-
                 // SP must be >= 1
                 if self.sp == 0 {
                     // Error
@@ -685,16 +804,6 @@ impl CPU {
                 // err Rx,Ry
                 self.set_reg(reg_x_index, 1);
                 self.set_reg(reg_y_index, self.error_pc_reg as u32);
-
-                self.set_instruction_string(
-                    "err",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                );
             }
             0x46 => {
                 // exit
@@ -703,18 +812,16 @@ impl CPU {
                 self.halt = match identifier {
                     0 => HaltState::Success,
                     1 => HaltState::Failure,
-                    _ => panic!("Unknown identifier {identifier} for 0x46"),
+                    _ => {
+                        return self.fault(
+                            inst_pc,
+                            inst_word,
+                            format!("Unknown identifier {identifier} for 0x46"),
+                        )
+                    }
                 };
 
                 self.logs.push(format!("Sim: Halted with {identifier}"));
-
-                self.set_instruction_string(
-                    "exit",
-                    InstructionKind::SingleReg {
-                        x: identifier,
-                        size: None,
-                    },
-                );
             }
             0x47 => {
                 // clc/sec
@@ -723,124 +830,121 @@ impl CPU {
                 match identifier {
                     0 => self.set_carry(false),
                     1 => self.set_carry(true),
-                    _ => panic!("Unknown identifier {identifier} for 0x47"),
+                    _ => {
+                        return self.fault(
+                            inst_pc,
+                            inst_word,
+                            format!("Unknown identifier {identifier} for 0x47"),
+                        )
+                    }
                 };
-
-                self.set_instruction_string(
-                    if identifier == 0 { "clc" } else { "sec" },
-                    InstructionKind::None,
-                );
             }
             0x48 => {
                 // uivisible Rx,Ry
-                // Unimplemented
                 let reg_x = self.get_reg(reg_x_index);
                 let reg_y = self.get_reg(reg_y_index);
-                self.logs
-                    .push(format!("Sim: UIVISIBLE Rx: {reg_x} Ry: {reg_y}"));
-
-                self.set_instruction_string(
-                    "uivisible",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
+
+                let visible = reg_y != 0;
+                self.ui.visibility.insert(reg_x, visible);
+
+                self.logs.push(format!(
+                    "Sim: Setting UI element {reg_x:#X} {}",
+                    if visible { "visible" } else { "hidden" }
+                ));
             }
             0x49 => {
                 // gettime Rx
-                // Unimplemented
-                self.logs.push(format!("Sim: GETTIME"));
-
-                self.set_instruction_string(
-                    "gettime",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                )
-            }
-            0x50 => {
-                // adjfs Rx,Ry
-                // Unimplemented
-                let reg_x = self.get_reg(reg_x_index);
-                let reg_y = self.get_reg(reg_y_index);
+                let time = self.clock.milliseconds(self.cycles);
 
-                self.logs.push(format!(
-                    "Sim: Adjusting size of file {reg_x:#X} to {reg_y:#X}"
-                ));
+                self.set_reg(reg_x_index, time);
 
-                self.set_instruction_string(
-                    "adjfs",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
+                self.logs.push(format!("Sim: gettime {time}ms"));
             }
-            0x51 => {
-                // adjfo Rx,Ry
-                // Unimplemented
+            0x50..=0x52 => {
+                // adjfs Rx,Ry | adjfo Rx,Ry | adjlp Rx,Ry
                 let reg_x = self.get_reg(reg_x_index);
                 let reg_y = self.get_reg(reg_y_index);
 
-                self.logs.push(format!(
-                    "Sim: Adjusting offset of file {reg_x:#X} to {reg_y:#X}"
-                ));
+                let description = match inst_prefix_byte {
+                    0x50 => "size",
+                    0x51 => "offset",
+                    _ => "load pointer",
+                };
 
-                self.set_instruction_string(
-                    "adjfo",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
-            }
-            0x52 => {
-                // adjlp Rx,Ry
-                // Unimplemented
-                let reg_x = self.get_reg(reg_x_index);
-                let reg_y = self.get_reg(reg_y_index);
+                if self.file_state.slots.iter().any(|s| s.id == reg_x) {
+                    let adjustment = self.file_state.adjustments.entry(reg_x).or_default();
 
-                self.logs.push(format!(
-                    // TODO: What does this mean
-                    "Sim: Adjusting pmp address of file {reg_x:#X} to {reg_y:#X}"
-                ));
+                    match inst_prefix_byte {
+                        0x50 => adjustment.size = Some(reg_y),
+                        0x51 => adjustment.offset = Some(reg_y),
+                        _ => adjustment.load_pointer = Some(reg_y),
+                    }
 
-                self.set_instruction_string(
-                    "adjlp",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
+                    self.zero = true;
+
+                    self.logs.push(format!(
+                        "Sim: Adjusting {description} of file {reg_x:#X} to {reg_y:#X}"
+                    ));
+                } else {
+                    self.zero = false;
+
+                    self.logs.push(format!("Sim: Slot {reg_x:#X} not found"));
+                }
             }
             0x53 => {
                 // loadf Rx
-                // Unimplemented
                 let reg_x = self.get_reg(reg_x_index);
 
-                // Always indicate success
-                self.zero = true;
+                let Some(slot) = self.file_state.slots.iter().find(|s| s.id == reg_x) else {
+                    // No slot found, set error
+                    self.zero = false;
 
-                self.logs
-                    .push(format!("Sim: Loading file {reg_x:#X} into FPGA"));
+                    self.logs.push(format!("Sim: Slot {reg_x:#X} not found"));
+                    return;
+                };
 
-                self.set_instruction_string(
-                    "loadf",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                )
+                let Some(address) = self.file_state.load_address(slot) else {
+                    self.zero = false;
+
+                    self.logs
+                        .push(format!("Sim: Slot {reg_x:#X} has no load address"));
+                    return;
+                };
+
+                let data = match self.file_state.load_data(slot) {
+                    Ok(data) => data,
+                    Err(reason) => {
+                        // File could not be loaded, set error
+                        self.zero = false;
+
+                        self.logs.push(format!(
+                            "Sim: File {reg_x:#X} could not be loaded: {reason}"
+                        ));
+                        return;
+                    }
+                };
+
+                let len = data.len() as u32;
+
+                if let Some(size_maximum) = slot.size_maximum {
+                    if len > size_maximum {
+                        self.zero = false;
+
+                        self.logs.push(format!(
+                            "Sim: File {reg_x:#X} of length {len:#X} exceeds maximum size {size_maximum:#X}"
+                        ));
+                        return;
+                    }
+                }
+
+                self.bridge.write_bytes(address, &data);
+                self.cycles += transfer_cycles(len as u64);
+
+                self.zero = true;
+
+                self.logs.push(format!(
+                    "Sim: Loaded file {reg_x:#X} of length {len:#X} into FPGA at {address:#X}"
+                ));
             }
             0x54 | 0x55 => {
                 // getext Rx,Ry | getname Rx,Ry
@@ -882,34 +986,18 @@ impl CPU {
                 self.ram
                     .write_byte((reg_y + content.len()).to_lower_word(), 0);
 
+                if self.ram.is_out_of_bounds() {
+                    return;
+                }
+
                 self.logs.push(if is_extension {
                     format!("Sim: Getting file extension of {reg_x:#X}: {content}")
                 } else {
                     format!("Sim: Getting file name of {reg_x:#X}: {content}")
                 });
-
-                self.set_instruction_string(
-                    if is_extension { "getext" } else { "getname" },
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
             }
             0x56 => {
                 // open Rx,Ry
-                self.set_instruction_string(
-                    "open",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                );
-
                 if let FileLoadedState::Loaded { slot, .. } = self.file_state.loaded {
                     // File already open, error
                     self.logs
@@ -921,7 +1009,8 @@ impl CPU {
                 let reg_x = self.get_reg(reg_x_index);
 
                 if let Some(slot) = self.file_state.slots.iter().find(|s| s.id == reg_x) {
-                    let file_content = file_to_buffer(&slot.filename);
+                    // Seeks, reads, and copies are within the file as adjusted by adjfo and adjfs
+                    let file_content = self.file_state.load_data(slot);
 
                     if let Ok(data) = file_content {
                         // File successfully loaded
@@ -929,7 +1018,7 @@ impl CPU {
 
                         self.file_state.loaded = FileLoadedState::Loaded {
                             slot: reg_x,
-                            data,
+                            data: data.into(),
                             offset: 0,
                         };
 
@@ -958,8 +1047,6 @@ impl CPU {
             }
             0x57 => {
                 // close
-                self.set_instruction_string("close", InstructionKind::None);
-
                 if match self.file_state.loaded {
                     FileLoadedState::Loaded { .. } => false,
                     _ => true,
@@ -975,14 +1062,6 @@ impl CPU {
             }
             0x58 => {
                 // seek Rx
-                self.set_instruction_string(
-                    "seek",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                );
-
                 let reg_x = self.get_reg(reg_x_index) as usize;
 
                 if let FileLoadedState::Loaded {
@@ -1012,16 +1091,6 @@ impl CPU {
             }
             0x59 => {
                 // read Rx,Ry
-                self.set_instruction_string(
-                    "read",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                );
-
                 let reg_x = self.get_reg(reg_x_index) as usize;
                 let reg_y = self.get_reg(reg_y_index) as usize;
 
@@ -1052,6 +1121,12 @@ impl CPU {
                         self.ram.write_byte((reg_x + i).to_lower_word(), byte);
                     }
 
+                    if self.ram.is_out_of_bounds() {
+                        return;
+                    }
+
+                    self.cycles += transfer_cycles(reg_y as u64);
+
                     self.zero = true;
                 } else {
                     // No open file, throw error
@@ -1063,29 +1138,50 @@ impl CPU {
             }
             0x5A => {
                 // copy Rx,Ry
-                // Unimplemented
                 let reg_x = self.get_reg(reg_x_index);
-                let reg_y = self.get_reg(reg_y_index);
+                let reg_y = self.get_reg(reg_y_index) as usize;
 
-                // Always indicate success
-                self.zero = true;
-                if let FileLoadedState::Loaded { ref mut offset, .. } = self.file_state.loaded {
-                    *offset += reg_y as usize;
-                }
+                if let FileLoadedState::Loaded {
+                    data,
+                    ref mut offset,
+                    ..
+                } = &mut self.file_state.loaded
+                {
+                    if reg_y + *offset > data.len() {
+                        // Can't copy past end of file
+                        self.zero = false;
 
-                self.logs.push(format!(
-                    "Sim: Copying {reg_y:#X} bytes to address {reg_x:#X} in FPGA"
-                ));
+                        self.logs
+                            .push("Sim: Attempted to copy past end of file".into());
+                        return;
+                    }
 
-                self.set_instruction_string(
-                    "copy",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
+                    if reg_x as u64 + reg_y as u64 > 1 << 32 {
+                        // Range extends past the end of the bridge
+                        self.logs.push(format!(
+                            "Sim: copy of length {reg_y:#X} to {reg_x:#X} is out of range"
+                        ));
+
+                        return self.jump_to_error();
+                    }
+
+                    self.bridge
+                        .write_bytes(reg_x, &data[*offset..*offset + reg_y]);
+                    self.cycles += transfer_cycles(reg_y as u64);
+
+                    *offset += reg_y;
+                    self.zero = true;
+
+                    self.logs.push(format!(
+                        "Sim: Copied {reg_y:#X} bytes to address {reg_x:#X} in FPGA"
+                    ));
+                } else {
+                    // No open file, throw error
+                    self.logs
+                        .push("Sim: Attempted to copy when no open file exists".into());
+
+                    self.jump_to_error();
+                }
             }
             0x5B => {
                 // core Rx
@@ -1095,72 +1191,86 @@ impl CPU {
                 self.active_bitstream = Some(reg_x as usize);
 
                 self.logs.push(format!("Sim: Selected core {reg_x:#X}"));
-
-                self.set_instruction_string(
-                    "core",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                )
             }
             0x5C => {
                 // host Rx,Ry
-                // Unimplemented
                 let reg_x = self.get_reg(reg_x_index);
                 let reg_y = self.get_reg(reg_y_index);
 
-                self.logs.push(format!(
-                    "Sim: Performing command {reg_x:#X} with parameter {reg_y:#X} in FPGA"
-                ));
+                let name = command_name(reg_x).map_or_else(
+                    || format!("{reg_x:#X}"),
+                    |name| format!("{name} ({reg_x:#X})"),
+                );
 
-                self.set_instruction_string(
-                    "host",
-                    InstructionKind::DoubleReg {
-                        x: reg_x_index,
-                        y: reg_y_index,
-                        mem_direction_into_reg: None,
-                        size: None,
-                    },
-                )
+                if self.speculative {
+                    self.host_skipped = true;
+
+                    self.logs.push(format!(
+                        "Sim: Command {name} with parameter {reg_y:#X} will be sent when stepped"
+                    ));
+                    return;
+                }
+
+                let result = self.host.borrow_mut().host_command(reg_x, reg_y);
+
+                match result {
+                    Ok(()) => {
+                        self.zero = true;
+
+                        self.logs.push(format!(
+                            "Sim: Performed command {name} with parameter {reg_y:#X} in FPGA"
+                        ));
+                    }
+                    Err(reason) => {
+                        self.zero = false;
+
+                        self.logs.push(format!(
+                            "Sim: Command {name} with parameter {reg_y:#X} failed: {reason}"
+                        ));
+                    }
+                }
             }
             0x5D => {
                 // queryslot Rx
-                // Unimplemented
                 let reg_x = self.get_reg(reg_x_index);
 
-                // Always indicate success
-                self.zero = true;
+                // Z is set only if the slot exists and its file, with any adjustments, is present
+                let result = match self.file_state.slots.iter().find(|s| s.id == reg_x) {
+                    Some(slot) => self.file_state.data_length(slot).map(|len| (slot, len)),
+                    None => Err("slot not found".into()),
+                };
 
-                self.logs.push(format!("Sim: Queried slot {reg_x:#X}"));
+                match result {
+                    Ok((slot, len)) => {
+                        self.zero = true;
 
-                self.set_instruction_string(
-                    "queryslot",
-                    InstructionKind::SingleReg {
-                        x: reg_x_index,
-                        size: None,
-                    },
-                )
+                        let kind = if slot.deferload { "deferred " } else { "" };
+
+                        self.logs.push(format!(
+                            "Sim: Queried {kind}slot {reg_x:#X}: present with length {len:#X}"
+                        ));
+                    }
+                    Err(reason) => {
+                        self.zero = false;
+
+                        self.logs
+                            .push(format!("Sim: Queried slot {reg_x:#X}: {reason}"));
+                    }
+                }
             }
             _ => {
                 match inst_prefix_upper_nibble {
                     0x6..=0xA => {
-                        self.jump_inst(
-                            inst_prefix_byte,
-                            inst_suffix_byte,
-                            inst_prefix_upper_nibble,
-                            true,
-                            |zero, carry| {
-                                match inst_prefix_upper_nibble {
-                                    0x6 => true,   // jp n
-                                    0x7 => !zero,  // jp nz
-                                    0x8 => zero,   // jp z
-                                    0x9 => !carry, // jp nc
-                                    0xA => carry,  // jp c
-                                    _ => unreachable!(),
-                                }
-                            },
-                        );
+                        self.jump_inst(inst_prefix_byte, inst_suffix_byte, |zero, carry| {
+                            match inst_prefix_upper_nibble {
+                                0x6 => true,   // jp n
+                                0x7 => !zero,  // jp nz
+                                0x8 => zero,   // jp z
+                                0x9 => !carry, // jp nc
+                                0xA => carry,  // jp c
+                                _ => unreachable!(),
+                            }
+                        });
                     }
                     0xB..=0xF => {
                         self.call_inst(inst_prefix_byte, inst_suffix_byte, |zero, carry| {
@@ -1173,48 +1283,12 @@ impl CPU {
                                 _ => unreachable!(),
                             }
                         });
-
-                        self.set_instruction_string(
-                            "call",
-                            match inst_prefix_upper_nibble {
-                                0xB => InstructionKind::Jump {
-                                    immediate: None,
-                                    always: true,
-                                    zero: None,
-                                    carry: None,
-                                },
-                                0xC => InstructionKind::Jump {
-                                    immediate: None,
-                                    always: false,
-                                    zero: Some(false),
-                                    carry: None,
-                                },
-                                0xD => InstructionKind::Jump {
-                                    immediate: None,
-                                    always: false,
-                                    zero: Some(true),
-                                    carry: None,
-                                },
-                                0xE => InstructionKind::Jump {
-                                    immediate: None,
-                                    always: false,
-                                    zero: None,
-                                    carry: Some(false),
-                                },
-                                0xF => InstructionKind::Jump {
-                                    immediate: None,
-                                    always: false,
-                                    zero: None,
-                                    carry: Some(true),
-                                },
-                                _ => unreachable!(),
-                            },
-                        );
-                    }
-                    _ => {
-                        // Do nothing
-                        todo!("Unimplemented {inst_prefix_byte:#X}")
                     }
+                    _ => self.fault(
+                        inst_pc,
+                        inst_word,
+                        format!("Unknown instruction {inst_prefix_byte:#X}"),
+                    ),
                 }
             }
         }
@@ -1237,7 +1311,11 @@ impl CPU {
         let reg_x = self.get_reg(reg_x_index);
 
         let address = if second_value_is_immed {
-            self.pc_word()
+            let Some(address) = self.pc_word() else {
+                return;
+            };
+
+            address
         } else {
             self.get_reg(reg_y_index).to_lower_word()
         };
@@ -1250,33 +1328,18 @@ impl CPU {
             }
         } else {
             let value = match size {
-                DataSize::Byte => self.ram.read_byte(address).into(),
-                DataSize::Word => self.ram.read_word(address).into(),
-                DataSize::Long => self.ram.read_long(address),
+                DataSize::Byte => self.ram.checked_read_byte(address).map(u32::from),
+                DataSize::Word => self.ram.checked_read_word(address).map(u32::from),
+                DataSize::Long => self.ram.checked_read_long(address),
+            };
+
+            let Some(value) = value else {
+                return;
             };
 
             self.set_reg(reg_x_index, value);
             self.set_zero(value);
         }
-
-        self.set_instruction_string(
-            "ld",
-            if second_value_is_immed {
-                InstructionKind::RegMem {
-                    x: reg_x_index,
-                    address,
-                    direction_into_reg: !write_mem,
-                    size,
-                }
-            } else {
-                InstructionKind::DoubleReg {
-                    x: reg_x_index,
-                    y: reg_y_index,
-                    mem_direction_into_reg: Some(!write_mem),
-                    size: Some(size),
-                }
-            },
-        )
     }
 
     ///
@@ -1285,7 +1348,6 @@ impl CPU {
     /// `bit32_immed` only applies if second argument is immediate
     fn alu_immediate_or_reg_inst<T: Fn(u32, u32) -> (u32, bool)>(
         &mut self,
-        inst_name: &str,
         inst_suffix_byte: u8,
         set_carry: bool,
         is_register: bool,
@@ -1300,11 +1362,17 @@ impl CPU {
             (self.get_reg(reg_y_index), inst_suffix_byte & 0xF)
         } else {
             // Immediate
-            if bit32_immed {
-                (self.pc_long(), inst_suffix_byte)
+            let immediate = if bit32_immed {
+                self.pc_long()
             } else {
-                (self.pc_word() as u32, inst_suffix_byte)
-            }
+                self.pc_word().map(u32::from)
+            };
+
+            let Some(immediate) = immediate else {
+                return;
+            };
+
+            (immediate, inst_suffix_byte)
         };
 
         let (value, carry) = operation(self.get_reg(reg_x_index), input_value);
@@ -1316,24 +1384,6 @@ impl CPU {
             self.set_carry(carry);
         }
         self.set_zero(value);
-
-        self.set_instruction_string(
-            inst_name,
-            if is_register {
-                InstructionKind::DoubleReg {
-                    x: reg_x_index,
-                    y: reg_y_index,
-                    mem_direction_into_reg: None,
-                    size: None,
-                }
-            } else {
-                InstructionKind::Immediate {
-                    x: reg_x_index,
-                    n: input_value,
-                    size: None,
-                }
-            },
-        )
     }
 
     ///
@@ -1342,7 +1392,6 @@ impl CPU {
     /// `second_value_is_immed` considers the Y value to be an immediate, otherwise it's a register
     fn alu_double_value_inst<T: Fn(u32, u32) -> (u32, bool)>(
         &mut self,
-        inst_name: &str,
         inst_suffix_byte: u8,
         set_carry: bool,
         second_value_is_immed: bool,
@@ -1365,24 +1414,6 @@ impl CPU {
             self.set_carry(carry);
         }
         self.set_zero(value);
-
-        self.set_instruction_string(
-            inst_name,
-            if second_value_is_immed {
-                InstructionKind::Immediate {
-                    x: reg_x_index,
-                    n: reg_y_index as u32,
-                    size: None,
-                }
-            } else {
-                InstructionKind::DoubleReg {
-                    x: reg_x_index,
-                    y: reg_y_index,
-                    mem_direction_into_reg: None,
-                    size: None,
-                }
-            },
-        )
     }
 
     ///
@@ -1414,8 +1445,6 @@ impl CPU {
         &mut self,
         inst_prefix_byte: u8,
         inst_suffix_byte: u8,
-        inst_prefix_upper_nibble: u8,
-        log_instruction: bool,
         conditional: T,
     ) {
         let inst_prefix_byte = inst_prefix_byte & 0xF;
@@ -1428,45 +1457,6 @@ impl CPU {
             // Should jump
             self.pc = address;
         }
-
-        if log_instruction {
-            self.set_instruction_string(
-                "jp",
-                match inst_prefix_upper_nibble {
-                    0x6 => InstructionKind::Jump {
-                        immediate: Some(address),
-                        always: true,
-                        zero: None,
-                        carry: None,
-                    },
-                    0x7 => InstructionKind::Jump {
-                        immediate: Some(address),
-                        always: false,
-                        zero: Some(false),
-                        carry: None,
-                    },
-                    0x8 => InstructionKind::Jump {
-                        immediate: Some(address),
-                        always: false,
-                        zero: Some(true),
-                        carry: None,
-                    },
-                    0x9 => InstructionKind::Jump {
-                        immediate: Some(address),
-                        always: false,
-                        zero: None,
-                        carry: Some(false),
-                    },
-                    0xA => InstructionKind::Jump {
-                        immediate: Some(address),
-                        always: false,
-                        zero: None,
-                        carry: Some(true),
-                    },
-                    _ => unreachable!(),
-                },
-            );
-        }
     }
 
     ///
@@ -1480,23 +1470,106 @@ impl CPU {
     ) {
         if conditional(self.zero, self.carry) {
             // Should return
-            // SP must be < 31
-            if self.sp >= 31 {
-                // Error
-                self.logs.push(format!("Sim: Stack overflow"));
-
+            if !self.push_stack(self.pc as u32) {
                 return self.jump_to_error();
             }
 
-            self.stack[self.sp] = self.pc as u32;
+            self.jump_inst(inst_prefix_byte, inst_suffix_byte, |_, _| true);
+        }
+    }
+
+    // Util
 
-            self.sp += 1;
+    ///
+    /// Pushes `value` onto the stack. Returns false, logging the overflow, if the stack is full
+    ///
+    fn push_stack(&mut self, value: u32) -> bool {
+        // SP must be < stack depth
+        if self.sp >= self.stack.len() {
+            self.logs.push("Sim: Stack overflow".into());
 
-            self.jump_inst(inst_prefix_byte, inst_suffix_byte, 0, false, |_, _| true);
+            return false;
         }
+
+        self.stack[self.sp] = value;
+
+        self.sp += 1;
+
+        true
     }
 
-    // Util
+    fn fault(&mut self, pc: u16, opcode: u16, reason: String) {
+        self.formatted_instruction = format!("??? {opcode:#06X}");
+
+        self.halt_with_fault(pc, opcode, reason);
+    }
+
+    fn halt_with_fault(&mut self, pc: u16, opcode: u16, reason: String) {
+        self.logs.push(format!(
+            "ERROR: Fault at {pc:#06X} ({opcode:#06X}): {reason}"
+        ));
+
+        self.halt = HaltState::Fault { pc, opcode, reason };
+    }
+
+    ///
+    /// Applies the memory policies to an access made by the instruction at `pc`. Returns the hit if
+    /// the access matched a watchpoint
+    ///
+    fn memory_event(&mut self, pc: u16, opcode: u16, event: MemoryEvent) -> Option<WatchpointHit> {
+        if !matches!(self.halt, HaltState::Running) {
+            // The instruction, or an earlier event, already stopped execution
+            return None;
+        }
+
+        match event {
+            MemoryEvent::OutOfBounds { address, length } => {
+                let reason =
+                    format!("Access of {length} byte(s) at {address:#06X} is outside of memory");
+
+                match self.ram.bounds_policy {
+                    // Wrapped accesses never produce events
+                    BoundsPolicy::Wrap => {}
+                    BoundsPolicy::Fault => self.halt_with_fault(pc, opcode, reason),
+                    BoundsPolicy::ErrorJump => {
+                        self.logs.push(format!("Sim: {reason}"));
+
+                        self.jump_to_error();
+                    }
+                }
+            }
+            MemoryEvent::RomWrite { address, length } => {
+                let reason =
+                    format!("Write of {length} byte(s) at {address:#06X} clobbers ROM data");
+
+                match self.ram.rom_write_policy {
+                    // Allowed writes never produce events
+                    RomWritePolicy::Allow => {}
+                    RomWritePolicy::Warn => self.logs.push(format!(
+                        "ERROR: Clobbering ROM data at {address:#06X} ({length} byte(s), PC {pc:#06X})"
+                    )),
+                    RomWritePolicy::Fault => self.halt_with_fault(pc, opcode, reason),
+                }
+            }
+            MemoryEvent::Watchpoint(hit) => {
+                let WatchpointHit {
+                    index,
+                    address,
+                    length,
+                    write,
+                } = hit;
+                let access = if write { "write" } else { "read" };
+
+                self.logs.push(format!(
+                    "Sim: Watchpoint {index} hit by {access} of {length} byte(s) at {address:#06X} (PC {pc:#06X})"
+                ));
+
+                return Some(hit);
+            }
+        }
+
+        None
+    }
 
     fn jump_to_error(&mut self) {
         // Save erroring PC
@@ -1513,20 +1586,28 @@ impl CPU {
     //     value
     // }
 
-    fn pc_word(&mut self) -> u16 {
-        let value = self.ram.read_word(self.pc);
+    fn pc_word(&mut self) -> Option<u16> {
+        let value = self.ram.fetch_word(self.pc)?;
 
-        self.pc += 2;
+        self.advance_pc(2);
 
-        value
+        Some(value)
     }
 
-    fn pc_long(&mut self) -> u32 {
-        let value = self.ram.read_long(self.pc);
+    fn pc_long(&mut self) -> Option<u32> {
+        let value = self.ram.fetch_long(self.pc)?;
 
-        self.pc += 4;
+        self.advance_pc(4);
 
-        value
+        Some(value)
+    }
+
+    fn advance_pc(&mut self, amount: u16) {
+        self.pc = self.pc.wrapping_add(amount);
+
+        if self.ram.bounds_policy == BoundsPolicy::Wrap {
+            self.pc %= MEMORY_SIZE as u16;
+        }
     }
 
     fn set_zero(&mut self, value: u32) {
@@ -1547,94 +1628,6 @@ impl CPU {
 
     // Debugging
 
-    fn set_instruction_string(&mut self, inst_name: &str, kind: InstructionKind) {
-        // This is replicated from the beginning of step
-        // let [inst_prefix_byte, inst_suffix_byte] = inst_word.to_be_bytes();
-
-        // let inst_prefix_upper_nibble = (inst_prefix_byte >> 4) & 0xF;
-        // let reg_x_index = inst_suffix_byte & 0xF;
-        // let reg_y_index = (inst_suffix_byte >> 4) & 0xF;
-        let string = match kind {
-            InstructionKind::SingleReg { x, size } => {
-                let size = format_size(size);
-
-                format!("{inst_name}{size} R{x}")
-            }
-            InstructionKind::DoubleReg {
-                x,
-                y,
-                mem_direction_into_reg,
-                size,
-            } => {
-                let size = format_size(size);
-
-                if let Some(mem_direction_into_reg) = mem_direction_into_reg {
-                    // Memory write
-                    if mem_direction_into_reg {
-                        format!("{inst_name}{size} R{x},(R{y})")
-                    } else {
-                        format!("{inst_name}{size} (R{y}),R{x}")
-                    }
-                } else {
-                    // Reg to reg
-                    format!("{inst_name}{size} R{x},R{y}")
-                }
-            }
-            InstructionKind::Immediate { x, n, size } => {
-                let size = format_size(size);
-
-                format!("{inst_name}{size} R{x},#{n:#X}")
-            }
-            InstructionKind::RegMem {
-                x,
-                address,
-                direction_into_reg,
-                size,
-            } => {
-                if direction_into_reg {
-                    format!("{inst_name}{size} R{x},({address:#X})")
-                } else {
-                    format!("{inst_name}{size} ({address:#X}),R{x}")
-                }
-            }
-            InstructionKind::Jump {
-                immediate,
-                always,
-                zero,
-                carry,
-            } => {
-                let modifier = if always {
-                    ""
-                } else if let Some(zero) = zero {
-                    if zero {
-                        "z "
-                    } else {
-                        "nz "
-                    }
-                } else if let Some(carry) = carry {
-                    if carry {
-                        "c "
-                    } else {
-                        "nc "
-                    }
-                } else {
-                    unreachable!()
-                };
-
-                let immediate = if let Some(immediate) = immediate {
-                    format!("#{immediate:#X}")
-                } else {
-                    "".into()
-                };
-
-                format!("{inst_name} {modifier}{immediate}").trim().into()
-            }
-            InstructionKind::None => inst_name.into(),
-        };
-
-        self.formatted_instruction = string;
-    }
-
     // Loading
 
     pub fn load_file(
@@ -1644,6 +1637,15 @@ impl CPU {
     ) -> Result<Self, io::Error> {
         let buffer = file_to_buffer(path_str)?;
 
+        Ok(CPU::load_bytes(buffer, data_slots, slot_override))
+    }
+
+    /// Creates a CPU running the program `buffer`, as if it were loaded with [`CPU::load_file`]
+    pub fn load_bytes(
+        buffer: Vec<u8>,
+        data_slots: Option<Vec<DataSlot>>,
+        slot_override: Option<u32>,
+    ) -> Self {
         let (data_slots, default_slot) = if let Some(result) = data_slots.and_then(|vec| {
             if let Some(first) = vec.iter().next() {
                 Some((vec.clone(), Some(first.id)))
@@ -1669,7 +1671,7 @@ impl CPU {
         let mut work_regs = [0; 16];
         work_regs[0] = selected_slot;
 
-        Ok(CPU {
+        CPU {
             pc: 0x2,
             sp: 0,
             work_regs,
@@ -1677,16 +1679,25 @@ impl CPU {
             carry: false,
             zero: false,
             ram: Memory::from_bytes(buffer),
-            stack: [0; 32],
+            bridge: BridgeMemory::default(),
+            rng: Rng::default(),
+            stack: vec![0; DEFAULT_STACK_DEPTH],
             file_state: FileState {
                 slots: data_slots,
+                adjustments: HashMap::new(),
                 loaded: FileLoadedState::None,
             },
+            ui: UiState::default(),
             halt: HaltState::Running,
+            cycles: 0,
+            clock: ClockMode::default(),
             formatted_instruction: String::new(),
             logs: Vec::new(),
             active_bitstream: None,
-        })
+            host: Rc::new(RefCell::new(DefaultHostHandler)),
+            speculative: false,
+            host_skipped: false,
+        }
     }
 }
 
@@ -1699,11 +1710,3 @@ fn file_to_buffer(path_str: &str) -> Result<Vec<u8>, io::Error> {
 
     Ok(buffer)
 }
-
-fn format_size(size: Option<DataSize>) -> String {
-    if let Some(size) = size {
-        format!("{size}")
-    } else {
-        "".into()
-    }
-}
@@ -0,0 +1,306 @@
+use std::{collections::VecDeque, mem};
+
+use crate::{
+    breakpoint::Breakpoint,
+    cpu::{HaltState, StepResult, CPU},
+    disasm::decode,
+    mem::MEMORY_SIZE,
+    source::SourceMap,
+    symbols::Symbols,
+};
+
+/// Why a run stopped
+pub enum StopReason {
+    Halted,
+    /// The index of the breakpoint at the new PC
+    Breakpoint(usize),
+    /// The index of the watchpoint hit by the last instruction
+    Watchpoint(usize),
+    /// The requested location was reached
+    Reached,
+    /// The maximum number of steps were executed
+    Limit,
+    /// There is no earlier state to step back to
+    HistoryStart,
+}
+
+/// The maximum number of previous states kept for stepping backwards
+const HISTORY_LIMIT: usize = 1000;
+
+/// A state before an executed instruction
+struct Snapshot {
+    /// The state, without its logs. Memory and file contents are shared with later states
+    state: CPU,
+    /// The number of logs `state` had. Logs are only appended to, so they are restored from the
+    /// logs of a later state
+    log_count: usize,
+    /// The result of the instruction executed from `state`
+    result: StepResult,
+}
+
+/// Maintains two copies of the CPU state, one a step ahead, so the next instruction can be displayed
+pub struct Debugger {
+    pub state: CPU,
+    pub next_state: CPU,
+    /// The result of the step from `state` to `next_state`
+    next_result: StepResult,
+    /// Previous states, oldest first. Host commands are not undone when stepping back
+    history: VecDeque<Snapshot>,
+
+    pub breakpoints: Vec<Breakpoint>,
+    pub symbols: Symbols,
+    pub source: Option<SourceMap>,
+}
+
+impl Debugger {
+    pub fn new(state: CPU, symbols: Symbols, source: Option<SourceMap>) -> Self {
+        let (next_state, next_result) = look_ahead(&state);
+
+        Debugger {
+            state,
+            next_state,
+            next_result,
+            history: VecDeque::new(),
+            breakpoints: Vec::new(),
+            symbols,
+            source,
+        }
+    }
+
+    /// Executes one instruction, returning the result of that instruction
+    pub fn step(&mut self) -> StepResult {
+        if self.next_state.host_skipped {
+            // Host commands are only sent once they are actually executed
+            self.next_state = self.state.clone();
+            self.next_result = self.next_state.step();
+        }
+
+        let result = self.next_result;
+
+        let mut previous = mem::replace(&mut self.state, self.next_state.clone());
+        (self.next_state, self.next_result) = look_ahead(&self.state);
+
+        if self.history.len() >= HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+
+        let log_count = mem::take(&mut previous.logs).len();
+
+        self.history.push_back(Snapshot {
+            state: previous,
+            log_count,
+            result,
+        });
+
+        result
+    }
+
+    /// Restores the state before the last instruction. Returns the result of the undone
+    /// instruction, or None if there is no history
+    ///
+    /// Watchpoints, like breakpoints, are not part of the history. The current watchpoints are
+    /// kept, and if they differ from those the instruction ran with, it is executed ahead again to
+    /// find its result. Stepping forward again replays the undone instruction, without repeating
+    /// any host command
+    pub fn step_back(&mut self) -> Option<StepResult> {
+        let Snapshot {
+            state: mut previous,
+            log_count,
+            result,
+        } = self.history.pop_back()?;
+
+        previous.logs = self.state.logs[..log_count].to_vec();
+
+        let watchpoints_changed = previous.ram.watchpoints != self.state.ram.watchpoints;
+        previous.ram.watchpoints = self.state.ram.watchpoints.clone();
+
+        // The current state is the step after the previous state
+        self.next_state = mem::replace(&mut self.state, previous);
+        self.next_result = result;
+
+        if watchpoints_changed {
+            self.next_result = look_ahead(&self.state).1;
+        }
+
+        Some(self.next_result)
+    }
+
+    /// Steps backwards until the PC reaches a breakpoint, or an undone instruction hit a
+    /// watchpoint
+    pub fn reverse_run(&mut self) -> StopReason {
+        loop {
+            match self.step_back() {
+                None => return StopReason::HistoryStart,
+                Some(StepResult::Watchpoint(hit)) => return StopReason::Watchpoint(hit.index),
+                Some(_) => {}
+            }
+
+            if let Some(index) = self.breakpoint_hit() {
+                return StopReason::Breakpoint(index);
+            }
+        }
+    }
+
+    /// Executes up to `limit` instructions, stopping early if the CPU halts, a watchpoint is hit,
+    /// or the PC reaches a breakpoint
+    pub fn run(&mut self, limit: usize) -> StopReason {
+        self.run_until(limit, |_| false)
+    }
+
+    /// Executes the next instruction. If it is a `call` that is taken, runs until the call returns
+    pub fn step_over(&mut self, limit: usize) -> StopReason {
+        if !matches!(self.state.halt, HaltState::Running) {
+            return StopReason::Halted;
+        }
+
+        let pc = self.state.pc;
+        let sp = self.state.sp;
+        let is_call = decode(self.state.ram.bytes(), pc).is_call();
+
+        if let StepResult::Watchpoint(hit) = self.step() {
+            return StopReason::Watchpoint(hit.index);
+        }
+
+        if !is_call || self.state.sp <= sp {
+            // Not a call, or the call wasn't taken
+            return StopReason::Reached;
+        }
+
+        let return_address = pc.wrapping_add(2);
+
+        self.run_until(limit, |cpu| cpu.sp <= sp && cpu.pc == return_address)
+    }
+
+    /// Runs until the current subroutine returns. Returns None if there is no subroutine to return
+    /// from
+    pub fn finish(&mut self, limit: usize) -> Option<StopReason> {
+        // The newest return address on the stack. Values pushed by the subroutine are above it
+        let slot = (0..self.state.sp)
+            .rev()
+            .find(|&slot| is_return_address(&self.state, self.state.stack[slot]))?;
+        let return_address = self.state.stack[slot] as u16;
+
+        // Returning pops the return address off of the stack
+        Some(self.run_until(limit, |cpu| cpu.sp <= slot && cpu.pc == return_address))
+    }
+
+    /// Executes until the PC reaches the start of a different source line. Returns None if no
+    /// source is loaded
+    pub fn step_line(&mut self, limit: usize) -> Option<StopReason> {
+        // Taken so the source can be borrowed while running
+        let source = self.source.take()?;
+        let line = source.line_of(self.state.pc);
+
+        let reason = self.run_until(limit, |cpu| {
+            source
+                .line_at(cpu.pc)
+                .is_some_and(|new_line| Some(new_line) != line)
+        });
+
+        self.source = Some(source);
+
+        Some(reason)
+    }
+
+    /// Executes until the PC reaches `address`
+    pub fn run_to(&mut self, limit: usize, address: u16) -> StopReason {
+        self.run_until(limit, |cpu| cpu.pc == address)
+    }
+
+    /// Executes up to `limit` instructions, stopping early if the CPU halts, a watchpoint or
+    /// breakpoint is hit, or `reached` is true for the new state
+    fn run_until<T: Fn(&CPU) -> bool>(&mut self, limit: usize, reached: T) -> StopReason {
+        for _ in 0..limit {
+            if !matches!(self.state.halt, HaltState::Running) {
+                return StopReason::Halted;
+            }
+
+            if let StepResult::Watchpoint(hit) = self.step() {
+                return StopReason::Watchpoint(hit.index);
+            }
+
+            if let Some(index) = self.breakpoint_hit() {
+                return StopReason::Breakpoint(index);
+            }
+
+            if reached(&self.state) {
+                return StopReason::Reached;
+            }
+        }
+
+        StopReason::Limit
+    }
+
+    /// The index of the first breakpoint that stops before the next instruction
+    pub fn breakpoint_hit(&self) -> Option<usize> {
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| breakpoint.is_hit(&self.state))
+    }
+
+    /// Recomputes the next state after `state` has been modified
+    pub fn refresh(&mut self) {
+        (self.next_state, self.next_result) = look_ahead(&self.state);
+    }
+
+    /// Resolves a symbol name, `file:line` in the loaded source, or hex address
+    pub fn parse_address(&self, target: &str) -> Result<u16, String> {
+        let target = target.trim();
+
+        let address = match self.symbols.lookup(target) {
+            Some(address) => address,
+            None if target.contains(':') => return self.parse_source_line(target),
+            None => {
+                let digits = target.strip_prefix("0x").unwrap_or(target);
+
+                u32::from_str_radix(digits, 16)
+                    .map_err(|_| format!("Unknown address or label \"{target}\""))?
+            }
+        };
+
+        if address >= 0x2000 {
+            return Err(format!("Address {address:#X} is outside of memory"));
+        }
+
+        Ok(address as u16)
+    }
+
+    /// Resolves `file:line` to the address of the first statement on or after that line
+    fn parse_source_line(&self, target: &str) -> Result<u16, String> {
+        let Some(source) = &self.source else {
+            return Err("No source is loaded".into());
+        };
+
+        let (file, line) = target.rsplit_once(':').unwrap_or(("", target));
+
+        if !file.is_empty() && file != source.name {
+            return Err(format!("Unknown source file \"{file}\""));
+        }
+
+        let line = line
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid line \"{line}\""))?;
+
+        source
+            .address_of(line)
+            .ok_or_else(|| format!("No code at or after {}:{line}", source.name))
+    }
+}
+
+/// Whether `value`, an entry on the stack, is the address following a `call` instruction
+pub fn is_return_address(state: &CPU, value: u32) -> bool {
+    (2..MEMORY_SIZE as u32).contains(&value)
+        && value & 1 == 0
+        && decode(state.ram.bytes(), value as u16 - 2).is_call()
+}
+
+/// Executes the instruction after `state` on a copy, without sending host commands
+fn look_ahead(state: &CPU) -> (CPU, StepResult) {
+    let mut next_state = state.clone();
+    next_state.speculative = true;
+
+    let result = next_state.step();
+    next_state.speculative = false;
+
+    (next_state, result)
+}
@@ -0,0 +1,376 @@
+use std::fmt::Display;
+
+use crate::symbols::Symbols;
+
+/// A jump, call, or return condition
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    NotZero,
+    Zero,
+    NotCarry,
+    Carry,
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Condition::NotZero => "nz",
+            Condition::Zero => "z",
+            Condition::NotCarry => "nc",
+            Condition::Carry => "c",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// Rx
+    Register(u8),
+    /// (Rx)
+    RegisterIndirect(u8),
+    /// #n
+    Immediate(u32),
+    /// (nnnn)
+    Memory(u16),
+    /// The destination of a jump or call
+    Target(u16),
+    Condition(Condition),
+    /// A plain number, such as an exit code
+    Value(u32),
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(x) => write!(f, "R{x}"),
+            Operand::RegisterIndirect(x) => write!(f, "(R{x})"),
+            Operand::Immediate(n) => write!(f, "#{n:#X}"),
+            Operand::Memory(address) => write!(f, "({address:#X})"),
+            Operand::Target(address) => write!(f, "{address:#06X}"),
+            Operand::Condition(condition) => write!(f, "{condition}"),
+            Operand::Value(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A decoded instruction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: u16,
+    /// The instruction word, followed by any immediate words
+    pub words: Vec<u16>,
+    /// `???` if the word is not a valid instruction
+    pub mnemonic: &'static str,
+    pub operands: Vec<Operand>,
+}
+
+impl Instruction {
+    /// The size of the instruction in bytes
+    pub fn size(&self) -> u16 {
+        self.words.len() as u16 * 2
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.mnemonic != "???"
+    }
+
+    /// The destination of a jump or call
+    pub fn target(&self) -> Option<u16> {
+        self.operands.iter().find_map(|operand| match operand {
+            Operand::Target(address) => Some(*address),
+            _ => None,
+        })
+    }
+
+    /// Whether this is a call, conditional or not
+    pub fn is_call(&self) -> bool {
+        self.mnemonic == "call"
+    }
+
+    ///
+    /// Formats the instruction with addresses and immediates replaced by symbol names
+    ///
+    /// Immediates below 0x100 are left as numbers, as they are more often counts than addresses
+    pub fn to_symbolic_string(&self, symbols: &Symbols) -> String {
+        let mut text = self.mnemonic.to_string();
+
+        for (i, operand) in self.operands.iter().enumerate() {
+            text += if i == 0 { " " } else { "," };
+
+            text += &match *operand {
+                Operand::Target(address) => match symbols.name_of(address as u32) {
+                    Some(name) => name.to_string(),
+                    None => operand.to_string(),
+                },
+                Operand::Memory(address) => match symbols.name_of(address as u32) {
+                    Some(name) => format!("({name})"),
+                    None => operand.to_string(),
+                },
+                Operand::Immediate(value) if value >= 0x100 => match symbols.name_of(value) {
+                    Some(name) => format!("#{name}"),
+                    None => operand.to_string(),
+                },
+                _ => operand.to_string(),
+            };
+        }
+
+        text
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.mnemonic)?;
+
+        for (i, operand) in self.operands.iter().enumerate() {
+            f.write_str(if i == 0 { " " } else { "," })?;
+            write!(f, "{operand}")?;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// Decodes the instruction at `address` in `bytes`, which holds memory starting at address 0
+///
+/// Bytes past the end of `bytes` are read as 0
+pub fn decode(bytes: &[u8], address: u16) -> Instruction {
+    let read_word = |offset: u16| {
+        let address = address.wrapping_add(offset) as usize;
+        let byte = |address: usize| bytes.get(address).copied().unwrap_or(0);
+
+        u16::from_le_bytes([byte(address), byte(address + 1)])
+    };
+
+    let word = read_word(0);
+    let [prefix, suffix] = word.to_be_bytes();
+
+    let x = suffix & 0xF;
+    let y = (suffix >> 4) & 0xF;
+
+    let mut words = vec![word];
+
+    let (mnemonic, operands) = match prefix {
+        0x00 => ("nop", vec![]),
+        0x02..=0x07 | 0x32..=0x37 => {
+            let mnemonic = match (prefix & 0xF) / 2 {
+                1 => "ld.b",
+                2 => "ld.w",
+                _ => "ld.l",
+            };
+
+            let memory = if prefix & 0x30 == 0 {
+                let address = read_word(2);
+                words.push(address);
+
+                Operand::Memory(address)
+            } else {
+                Operand::RegisterIndirect(y)
+            };
+
+            // Odd prefixes write to memory
+            if prefix & 1 == 0 {
+                (mnemonic, vec![Operand::Register(x), memory])
+            } else {
+                (mnemonic, vec![memory, Operand::Register(x)])
+            }
+        }
+        0x08..=0x0F | 0x18..=0x1F | 0x28..=0x2F => {
+            let mnemonic =
+                ["ld", "and", "or", "xor", "add", "sub", "cmp", "bit"][(prefix & 0x7) as usize];
+
+            let value = match prefix & 0xF0 {
+                0x00 => {
+                    let immediate = read_word(2);
+                    words.push(immediate);
+
+                    Operand::Immediate(immediate as u32)
+                }
+                0x10 => {
+                    let lower = read_word(2);
+                    let upper = read_word(4);
+                    words.extend([lower, upper]);
+
+                    Operand::Immediate(((upper as u32) << 16) | lower as u32)
+                }
+                _ => Operand::Register(y),
+            };
+
+            (mnemonic, vec![Operand::Register(x), value])
+        }
+        0x20..=0x27 => {
+            let mnemonic = ["asl", "lsr", "rol", "ror"][(prefix & 0x3) as usize];
+
+            let amount = if prefix < 0x24 {
+                Operand::Register(y)
+            } else {
+                Operand::Immediate(y as u32 + 1)
+            };
+
+            (mnemonic, vec![Operand::Register(x), amount])
+        }
+        0x40 | 0x43 | 0x44 | 0x49 | 0x53 | 0x58 | 0x5B | 0x5D => {
+            let mnemonic = match prefix {
+                0x40 => "printf",
+                0x43 => "push",
+                0x44 => "pop",
+                0x49 => "gettime",
+                0x53 => "loadf",
+                0x58 => "seek",
+                0x5B => "core",
+                _ => "queryslot",
+            };
+
+            (mnemonic, vec![Operand::Register(x)])
+        }
+        0x41 if y <= 5 => {
+            let mnemonic = ["hex.b", "hex.w", "hex.l", "dec.b", "dec.w", "dec.l"][y as usize];
+
+            (mnemonic, vec![Operand::Register(x)])
+        }
+        0x42 => match x {
+            0 => ("ret", vec![]),
+            1..=4 => ("ret", vec![Operand::Condition(condition(x))]),
+            _ => invalid(),
+        },
+        0x46 if x <= 1 => ("exit", vec![Operand::Value(x as u32)]),
+        0x47 if x == 0 => ("clc", vec![]),
+        0x47 if x == 1 => ("sec", vec![]),
+        0x57 => ("close", vec![]),
+        0x10
+        | 0x11
+        | 0x38..=0x3F
+        | 0x45
+        | 0x48
+        | 0x50..=0x52
+        | 0x54..=0x56
+        | 0x59
+        | 0x5A
+        | 0x5C => {
+            let mnemonic = match prefix {
+                0x10 => "rset",
+                0x11 => "crc",
+                0x38 => "mul",
+                0x39 => "test",
+                0x3A => "pmpw",
+                0x3B => "pmpr",
+                0x3C => "pmpbw",
+                0x3D => "xfill",
+                0x3E => "div",
+                0x3F => "rfill",
+                0x45 => "err",
+                0x48 => "uivisible",
+                0x50 => "adjfs",
+                0x51 => "adjfo",
+                0x52 => "adjlp",
+                0x54 => "getext",
+                0x55 => "getname",
+                0x56 => "open",
+                0x59 => "read",
+                0x5A => "copy",
+                _ => "host",
+            };
+
+            (mnemonic, vec![Operand::Register(x), Operand::Register(y)])
+        }
+        0x60..=0xFF => {
+            let nibble = prefix >> 4;
+            let target = Operand::Target((word & 0xFFF) * 2);
+
+            let (mnemonic, base) = if nibble < 0xB {
+                ("jp", 0x6)
+            } else {
+                ("call", 0xB)
+            };
+
+            match nibble - base {
+                0 => (mnemonic, vec![target]),
+                condition_index => (
+                    mnemonic,
+                    vec![Operand::Condition(condition(condition_index)), target],
+                ),
+            }
+        }
+        _ => invalid(),
+    };
+
+    Instruction {
+        address,
+        words,
+        mnemonic,
+        operands,
+    }
+}
+
+///
+/// Decodes consecutive instructions from `start` up to, but not including, `end`
+///
+/// Data is decoded as if it were instructions
+pub fn disassemble(bytes: &[u8], start: u16, end: u16) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut address = start as u32;
+
+    while address < end as u32 {
+        let instruction = decode(bytes, address as u16);
+        address += instruction.size() as u32;
+
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+///
+/// Decodes up to `before` instructions leading up to `address`, the instruction at `address`, and
+/// `after` instructions following it
+///
+/// Instructions before `address` are found by decoding from 0, so any that overlap `address` are
+/// skipped
+pub fn disassemble_around(
+    bytes: &[u8],
+    address: u16,
+    before: usize,
+    after: usize,
+) -> Vec<Instruction> {
+    let mut instructions = disassemble(bytes, 0, address);
+
+    if instructions
+        .last()
+        .is_some_and(|last| last.address as u32 + last.size() as u32 > address as u32)
+    {
+        instructions.pop();
+    }
+
+    let skip = instructions.len().saturating_sub(before);
+    instructions.drain(..skip);
+
+    let mut address = address as u32;
+
+    for _ in 0..=after {
+        if address as usize >= bytes.len() {
+            break;
+        }
+
+        let instruction = decode(bytes, address as u16);
+        address += instruction.size() as u32;
+
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+/// Decodes a condition index from a `ret`, `jp`, or `call` instruction. 1 is nz, through 4 is c
+fn condition(index: u8) -> Condition {
+    match index {
+        1 => Condition::NotZero,
+        2 => Condition::Zero,
+        3 => Condition::NotCarry,
+        _ => Condition::Carry,
+    }
+}
+
+fn invalid() -> (&'static str, Vec<Operand>) {
+    ("???", vec![])
+}
@@ -0,0 +1,74 @@
+use serde::Serialize;
+
+/// Stops the core
+pub const HOST_RESET: u32 = 0x4000;
+/// Starts the core
+pub const HOST_RUN: u32 = 0x4001;
+/// Initializes and starts the core. Used for the first run after loading
+pub const HOST_INIT: u32 = 0x4002;
+
+///
+/// Receives the commands sent to the FPGA by `host Rx,Ry`
+///
+/// Returning `Err` rejects the command, reporting failure to the program
+pub trait HostHandler {
+    fn host_command(&mut self, command: u32, parameter: u32) -> Result<(), String>;
+}
+
+impl<F: FnMut(u32, u32) -> Result<(), String>> HostHandler for F {
+    fn host_command(&mut self, command: u32, parameter: u32) -> Result<(), String> {
+        self(command, parameter)
+    }
+}
+
+/// Accepts every command
+#[derive(Default)]
+pub struct DefaultHostHandler;
+
+impl HostHandler for DefaultHostHandler {
+    fn host_command(&mut self, _command: u32, _parameter: u32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct HostCall {
+    pub command: u32,
+    pub parameter: u32,
+    pub accepted: bool,
+}
+
+/// Records every command, rejecting those listed in `rejected_commands`
+#[derive(Default)]
+pub struct RecordingHostHandler {
+    pub calls: Vec<HostCall>,
+    pub rejected_commands: Vec<u32>,
+}
+
+impl HostHandler for RecordingHostHandler {
+    fn host_command(&mut self, command: u32, parameter: u32) -> Result<(), String> {
+        let accepted = !self.rejected_commands.contains(&command);
+
+        self.calls.push(HostCall {
+            command,
+            parameter,
+            accepted,
+        });
+
+        if accepted {
+            Ok(())
+        } else {
+            Err(format!("Command {command:#X} rejected"))
+        }
+    }
+}
+
+/// The name of a known host command
+pub fn command_name(command: u32) -> Option<&'static str> {
+    match command {
+        HOST_RESET => Some("host_reset"),
+        HOST_RUN => Some("host_run"),
+        HOST_INIT => Some("host_init"),
+        _ => None,
+    }
+}
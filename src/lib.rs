@@ -1,4 +1,14 @@
 pub mod apf;
+pub mod assembler;
+pub mod breakpoint;
+pub mod bridge;
+pub mod clock;
 pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod host;
 pub mod mem;
+pub mod source;
+pub mod symbols;
+pub mod timing;
 pub mod util;
@@ -1,27 +1,39 @@
 use crate::tui::modes::App;
 use ::tui::{backend::CrosstermBackend, Terminal};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use serde::Serialize;
-use std::{io, process};
+use std::{cell::RefCell, fs, io, process, rc::Rc};
 
 use crate::tui::run_app;
 use chip32_sim::{
     apf::parse_json,
-    cpu::{FileLoadedState, HaltState, CPU},
+    clock::ClockMode,
+    cpu::{FileLoadedState, HaltState, StepResult, UiState, CPU, DEFAULT_STACK_DEPTH},
+    disasm::disassemble,
+    host::{HostCall, RecordingHostHandler},
+    mem::{BoundsPolicy, RomWritePolicy, Watchpoint, WatchpointHit, MEMORY_SIZE},
+    source::SourceMap,
+    symbols::Symbols,
+    timing::cycles_to_milliseconds,
+    util::{num::parse_number, rng::Rng},
 };
 
 mod tui;
 
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
+#[clap(subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The bin file to load
-    #[clap(short, long, value_parser)]
-    bin: String,
+    #[clap(short, long, value_parser, required = true)]
+    bin: Option<String>,
 
     /// The data slot file to load
     #[clap(short, long, value_parser)]
@@ -31,31 +43,135 @@ struct Args {
     #[clap(short = 's', long, value_parser)]
     data_slot: Option<u32>,
 
+    /// Seed for the random data generated by rfill. Runs with the same seed produce the same data
+    #[clap(long, value_parser)]
+    seed: Option<u32>,
+
+    /// The source of time for gettime. One of "virtual" (derived from executed cycles), "host" (wall clock), or a fixed number of milliseconds
+    #[clap(long, value_parser, default_value = "virtual")]
+    clock: ClockMode,
+
+    /// The maximum number of entries on the stack. Pushes and calls beyond this jump to the error vector
+    #[clap(long, value_parser, default_value_t = DEFAULT_STACK_DEPTH)]
+    stack_depth: usize,
+
+    /// How accesses past the end of memory are handled. One of "wrap", "fault", or "error" (jump to the error vector). With "fault", running the PC off of the end of memory exits with failure
+    #[clap(long, value_parser, default_value = "fault")]
+    bounds: BoundsPolicy,
+
+    /// How writes to the loaded program are handled. One of "allow", "warn", or "fault"
+    #[clap(long, value_parser, default_value = "warn")]
+    rom_writes: RomWritePolicy,
+
+    /// Stop when memory is accessed. Formatted as [r|w|rw:]start[-end] with hex addresses, such as w:1B00-1BFF. Can be provided multiple times
+    #[clap(long, value_parser)]
+    watch: Vec<Watchpoint>,
+
+    /// A symbol table to resolve labels from, with one hex value and name per line, or the .asm source of the bin file
+    #[clap(long, value_parser)]
+    symbols: Option<String>,
+
+    /// The .asm source of the bin file, shown in the TUI for source level stepping. Also used for symbols if --symbols is not provided
+    #[clap(long, value_parser)]
+    source: Option<String>,
+
+    /// A host command (such as 0x4001) to reject. Can be provided multiple times
+    #[clap(long, value_parser = parse_number)]
+    reject_host: Vec<u32>,
+
     /// Execute the simulation in JSON output mode
     #[clap(long)]
     json: bool,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a listing of the instructions in a bin file without executing it
+    Disasm {
+        /// The bin file to disassemble
+        #[clap(short, long, value_parser)]
+        bin: String,
+
+        /// The first address to decode
+        #[clap(long, value_parser = parse_number, default_value = "0")]
+        start: u32,
+
+        /// The address to stop decoding at. Defaults to the end of the bin file
+        #[clap(long, value_parser = parse_number)]
+        end: Option<u32>,
+
+        /// A symbol table or .asm source to label the listing with
+        #[clap(long, value_parser)]
+        symbols: Option<String>,
+    },
+}
+
 #[derive(Serialize)]
 struct JSONOutput {
+    halt: HaltState,
+    /// The watchpoint that stopped execution, if any
+    watchpoint: Option<WatchpointHit>,
+    cycles: u64,
+    estimated_milliseconds: f64,
     core: Option<usize>,
     logs: Vec<String>,
     file_state: FileLoadedState,
+    ui: UiState,
+    host_calls: Vec<HostCall>,
 }
 
 fn main() -> Result<(), io::Error> {
     let args = Args::parse();
 
+    if let Some(Command::Disasm {
+        bin,
+        start,
+        end,
+        symbols,
+    }) = args.command
+    {
+        return print_disassembly(&bin, start, end, symbols);
+    }
+
+    let bin = args.bin.expect("--bin is required");
+
     let slots = args
         .data_json
         .map_or(None, |json_path| Some(parse_json(&json_path)));
 
-    let mut cpu = CPU::load_file(&args.bin, slots, args.data_slot)?;
+    let mut cpu = CPU::load_file(&bin, slots, args.data_slot)?;
+
+    let source = match args.source {
+        Some(path) => Some(SourceMap::load(&path, cpu.ram.bytes()).map_err(io::Error::other)?),
+        None => None,
+    };
+
+    let symbols = match (args.symbols, &source) {
+        (Some(path), _) => Symbols::load(&path, cpu.ram.bytes()).map_err(io::Error::other)?,
+        (None, Some(source)) => source.symbols.clone(),
+        (None, None) => Symbols::default(),
+    };
+
+    cpu.clock = args.clock;
+    cpu.stack = vec![0; args.stack_depth];
+    cpu.ram.bounds_policy = args.bounds;
+    cpu.ram.rom_write_policy = args.rom_writes;
+    cpu.ram.watchpoints = args.watch;
+
+    if let Some(seed) = args.seed {
+        cpu.rng = Rng::new(seed);
+    }
+
+    let host = Rc::new(RefCell::new(RecordingHostHandler {
+        rejected_commands: args.reject_host,
+        ..Default::default()
+    }));
+    cpu.host = host.clone();
 
     if args.json {
-        let exit_code = execute_with_json(&mut cpu);
+        let (exit_code, watchpoint) = execute_with_json(&mut cpu);
 
-        println!("{}", build_json_output(&cpu));
+        println!("{}", build_json_output(&cpu, watchpoint, &host.borrow()));
 
         process::exit(exit_code as i32);
     }
@@ -68,7 +184,7 @@ fn main() -> Result<(), io::Error> {
 
     // create app and run it
     let app = App::default();
-    let res = run_app(&mut terminal, app, cpu);
+    let res = run_app(&mut terminal, app, cpu, symbols, source);
 
     // restore terminal
     disable_raw_mode()?;
@@ -82,27 +198,94 @@ fn main() -> Result<(), io::Error> {
     Ok(())
 }
 
-fn execute_with_json(cpu: &mut CPU) -> usize {
+fn print_disassembly(
+    path: &str,
+    start: u32,
+    end: Option<u32>,
+    symbols: Option<String>,
+) -> Result<(), io::Error> {
+    let bytes = fs::read(path)?;
+
+    let symbols = match symbols {
+        Some(path) => Symbols::load(&path, &bytes).map_err(io::Error::other)?,
+        None => Symbols::default(),
+    };
+
+    let end = end.unwrap_or((bytes.len() as u32).min(MEMORY_SIZE as u32));
+
+    // The end is exclusive, so it may be the end of memory
+    for (address, last) in [(start, MEMORY_SIZE - 1), (end, MEMORY_SIZE)] {
+        if address as usize > last {
+            return Err(io::Error::other(format!(
+                "Address {address:#X} is outside of memory"
+            )));
+        }
+    }
+
+    for instruction in disassemble(&bytes, start as u16, end as u16) {
+        if let Some(label) = symbols.label_at(instruction.address) {
+            println!("{label}:");
+        }
+
+        let words = instruction
+            .words
+            .iter()
+            .map(|word| format!("{word:04X}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        println!(
+            "{:04X}: {words:<15} {}",
+            instruction.address,
+            instruction.to_symbolic_string(&symbols)
+        );
+    }
+
+    Ok(())
+}
+
+///
+/// Runs the program to completion. The returned exit code is:
+///
+/// * 0 - Exited with success
+/// * 1 - Exited with failure
+/// * 2 - Did not terminate
+/// * 3 - Faulted on an invalid instruction
+/// * 4 - Stopped by a watchpoint
+fn execute_with_json(cpu: &mut CPU) -> (usize, Option<WatchpointHit>) {
     // No GUI, just run up to 1 million cycles
     for _ in 0..1_000_000 {
-        cpu.step();
+        if let StepResult::Watchpoint(hit) = cpu.step() {
+            return (4, Some(hit));
+        }
 
         match cpu.halt {
-            HaltState::Success => return 0,
-            HaltState::Failure => return 1,
+            HaltState::Success => return (0, None),
+            HaltState::Failure => return (1, None),
+            HaltState::Fault { .. } => return (3, None),
             _ => {}
         }
     }
 
     // Did not terminate
-    return 2;
+    return (2, None);
 }
 
-fn build_json_output(cpu: &CPU) -> String {
+fn build_json_output(
+    cpu: &CPU,
+    watchpoint: Option<WatchpointHit>,
+    host: &RecordingHostHandler,
+) -> String {
     let output = JSONOutput {
+        halt: cpu.halt.clone(),
+        watchpoint,
+        cycles: cpu.cycles,
+        estimated_milliseconds: cycles_to_milliseconds(cpu.cycles),
         core: cpu.active_bitstream,
         logs: cpu.logs.clone(),
         file_state: cpu.file_state.loaded.clone(),
+        ui: cpu.ui.clone(),
+        host_calls: host.calls.clone(),
     };
 
     return serde_json::to_string(&output).expect("Couldn't generate JSON output");
@@ -1,86 +1,369 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc, str::FromStr};
+
+use serde::Serialize;
+
+/// The size of CHIP32 memory in bytes
+pub const MEMORY_SIZE: usize = 8 * 1024;
+
+/// How accesses that extend past the end of memory are handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoundsPolicy {
+    /// Addresses wrap around to the start of memory
+    Wrap,
+    /// The CPU halts with a fault
+    #[default]
+    Fault,
+    /// The CPU jumps to the error vector
+    ErrorJump,
+}
+
+impl FromStr for BoundsPolicy {
+    type Err = String;
+
+    /// Parses `wrap`, `fault`, or `error`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(BoundsPolicy::Wrap),
+            "fault" => Ok(BoundsPolicy::Fault),
+            "error" => Ok(BoundsPolicy::ErrorJump),
+            other => Err(format!(
+                "Expected \"wrap\", \"fault\", or \"error\". Found \"{other}\""
+            )),
+        }
+    }
+}
+
+/// How writes to the region of memory the program was loaded into are handled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RomWritePolicy {
+    /// The write is performed silently
+    Allow,
+    /// The write is performed and logged
+    #[default]
+    Warn,
+    /// The write is skipped and the CPU halts with a fault
+    Fault,
+}
+
+impl FromStr for RomWritePolicy {
+    type Err = String;
+
+    /// Parses `allow`, `warn`, or `fault`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(RomWritePolicy::Allow),
+            "warn" => Ok(RomWritePolicy::Warn),
+            "fault" => Ok(RomWritePolicy::Fault),
+            other => Err(format!(
+                "Expected \"allow\", \"warn\", or \"fault\". Found \"{other}\""
+            )),
+        }
+    }
+}
+
+/// A memory access that the CPU must handle according to the memory policies
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryEvent {
+    /// An access that extended past the end of memory
+    OutOfBounds {
+        address: u16,
+        /// The number of bytes accessed
+        length: usize,
+    },
+    /// A write that overlapped the loaded program
+    RomWrite {
+        address: u16,
+        /// The number of bytes written
+        length: usize,
+    },
+    /// An access that matched a watchpoint
+    Watchpoint(WatchpointHit),
+}
+
+/// The accesses a watchpoint stops on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Both reads and writes
+    Access,
+}
+
+/// Stops execution after an instruction accesses the inclusive range `start..=end`. Instruction
+/// fetches do not trigger watchpoints
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub kind: WatchKind,
+}
+
+impl Watchpoint {
+    fn matches(&self, address: usize, write: bool) -> bool {
+        let kind_matches = match self.kind {
+            WatchKind::Read => !write,
+            WatchKind::Write => write,
+            WatchKind::Access => true,
+        };
+
+        kind_matches && (self.start as usize..=self.end as usize).contains(&address)
+    }
+}
+
+impl FromStr for Watchpoint {
+    type Err = String;
+
+    /// Parses `[r|w|rw:]start[-end]`, where addresses are hex. Without a kind, both reads and writes
+    /// are watched
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, range) = match s.split_once(':') {
+            Some(("r", range)) => (WatchKind::Read, range),
+            Some(("w", range)) => (WatchKind::Write, range),
+            Some(("rw", range)) => (WatchKind::Access, range),
+            Some((kind, _)) => {
+                return Err(format!(
+                    "Expected a kind of \"r\", \"w\", or \"rw\". Found \"{kind}\""
+                ))
+            }
+            None => (WatchKind::Access, s),
+        };
+
+        let parse_address = |address: &str| {
+            let address = address.trim();
+            let digits = address.strip_prefix("0x").unwrap_or(address);
+
+            u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid address \"{address}\""))
+        };
+
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (parse_address(start)?, parse_address(end)?),
+            None => {
+                let address = parse_address(range)?;
+                (address, address)
+            }
+        };
+
+        if start > end {
+            return Err(format!("Range start {start:#06X} is after end {end:#06X}"));
+        }
+
+        Ok(Watchpoint { start, end, kind })
+    }
+}
+
+impl Display for Watchpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            WatchKind::Read => "r",
+            WatchKind::Write => "w",
+            WatchKind::Access => "rw",
+        };
+
+        if self.start == self.end {
+            write!(f, "{kind}:{:#06X}", self.start)
+        } else {
+            write!(f, "{kind}:{:#06X}-{:#06X}", self.start, self.end)
+        }
+    }
+}
+
+/// A watched access
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct WatchpointHit {
+    /// The index of the watchpoint in `Memory::watchpoints`
+    pub index: usize,
+    pub address: u16,
+    /// The number of bytes accessed
+    pub length: usize,
+    pub write: bool,
+}
+
+/// CHIP32 memory. The contents are shared between clones until one is written to, so keeping
+/// previous states is cheap
 #[derive(Clone)]
 pub struct Memory {
-    ram: [u8; 8 * 1024],
+    ram: Rc<[u8; MEMORY_SIZE]>,
     rom_size: usize,
+
+    pub bounds_policy: BoundsPolicy,
+    pub rom_write_policy: RomWritePolicy,
+    pub watchpoints: Vec<Watchpoint>,
+    /// Accesses since the last call to `take_events`. Out of bounds accesses that are not wrapped
+    /// are skipped
+    events: RefCell<Vec<MemoryEvent>>,
 }
 
 impl Memory {
     pub fn from_bytes(bytes: Vec<u8>) -> Self {
-        let mut ram = [0; 8 * 1024];
+        let mut ram = [0; MEMORY_SIZE];
 
         bytes
             .iter()
+            .take(MEMORY_SIZE)
             .enumerate()
             .for_each(|(i, byte)| ram[i] = *byte);
 
         Memory {
-            ram,
+            ram: Rc::new(ram),
             rom_size: bytes.len(),
+            bounds_policy: BoundsPolicy::default(),
+            rom_write_policy: RomWritePolicy::default(),
+            watchpoints: Vec::new(),
+            events: RefCell::new(Vec::new()),
         }
     }
 
+    /// Reads a byte. Out of bounds reads that are not wrapped return 0
     pub fn read_byte(&self, address: u16) -> u8 {
-        let address = address as usize;
-
-        self.ram[address]
+        self.checked_read_byte(address).unwrap_or(0)
     }
 
+    /// Reads a word. Out of bounds reads that are not wrapped return 0
     pub fn read_word(&self, address: u16) -> u16 {
-        let address = address as usize;
-
-        u16::from_le_bytes(
-            self.ram[address..address + 2]
-                .try_into()
-                .expect("Slice wasn't of length 2"),
-        )
+        self.checked_read_word(address).unwrap_or(0)
     }
 
+    /// Reads a long. Out of bounds reads that are not wrapped return 0
     pub fn read_long(&self, address: u16) -> u32 {
-        let address = address as usize;
+        self.checked_read_long(address).unwrap_or(0)
+    }
+
+    /// Reads a byte, or `None` if the read is out of bounds and not wrapped
+    pub fn checked_read_byte(&self, address: u16) -> Option<u8> {
+        self.read_bytes(address, true).map(u8::from_le_bytes)
+    }
+
+    /// Reads a word, or `None` if the read is out of bounds and not wrapped
+    pub fn checked_read_word(&self, address: u16) -> Option<u16> {
+        self.read_bytes(address, true).map(u16::from_le_bytes)
+    }
+
+    /// Reads a long, or `None` if the read is out of bounds and not wrapped
+    pub fn checked_read_long(&self, address: u16) -> Option<u32> {
+        self.read_bytes(address, true).map(u32::from_le_bytes)
+    }
+
+    /// Reads an instruction word. Identical to `checked_read_word`, except watchpoints are ignored
+    pub fn fetch_word(&self, address: u16) -> Option<u16> {
+        self.read_bytes(address, false).map(u16::from_le_bytes)
+    }
 
-        u32::from_le_bytes(
-            self.ram[address..address + 4]
-                .try_into()
-                .expect("Slice wasn't of length 4"),
-        )
+    /// Reads an instruction immediate. Identical to `checked_read_long`, except watchpoints are
+    /// ignored
+    pub fn fetch_long(&self, address: u16) -> Option<u32> {
+        self.read_bytes(address, false).map(u32::from_le_bytes)
     }
 
-    // TODO: Log message when you clobber the ROM data
     pub fn write_byte(&mut self, address: u16, byte: u8) {
-        let address = address as usize;
+        self.write_bytes(address, [byte]);
+    }
+
+    pub fn write_word(&mut self, address: u16, word: u16) {
+        self.write_bytes(address, word.to_le_bytes());
+    }
+
+    pub fn write_long(&mut self, address: u16, word: u32) {
+        self.write_bytes(address, word.to_le_bytes());
+    }
+
+    /// The entire contents of memory. Unlike the read methods, this does not trigger watchpoints
+    pub fn bytes(&self) -> &[u8] {
+        &self.ram[..]
+    }
+
+    /// Returns and clears the accesses the CPU must handle
+    pub fn take_events(&self) -> Vec<MemoryEvent> {
+        self.events.take()
+    }
+
+    ///
+    /// Whether an access since the last call to `take_events` was out of bounds and not wrapped.
+    /// All further accesses are refused until then, so an instruction stops at its first bad access
+    pub fn is_out_of_bounds(&self) -> bool {
+        self.events
+            .borrow()
+            .iter()
+            .any(|event| matches!(event, MemoryEvent::OutOfBounds { .. }))
+    }
+
+    fn read_bytes<const N: usize>(&self, address: u16, watch: bool) -> Option<[u8; N]> {
+        if !self.check_bounds(address, N) {
+            return None;
+        }
 
-        if address < self.rom_size {
-            println!("ERROR: Clobbering ROM data");
+        if watch {
+            self.check_watchpoints(address, N, false);
         }
 
-        self.ram[address] = byte;
+        let mut bytes = [0; N];
+
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = self.ram[wrap_address(address, i)];
+        }
+
+        Some(bytes)
     }
 
-    pub fn write_word(&mut self, address: u16, word: u16) {
-        let address = address as usize;
+    fn write_bytes<const N: usize>(&mut self, address: u16, bytes: [u8; N]) {
+        if !self.check_bounds(address, N) {
+            return;
+        }
 
-        if address < self.rom_size {
-            println!("ERROR: Clobbering ROM data");
+        let clobbers_rom = (0..N).any(|i| wrap_address(address, i) < self.rom_size);
+
+        if clobbers_rom && self.rom_write_policy != RomWritePolicy::Allow {
+            self.events
+                .borrow_mut()
+                .push(MemoryEvent::RomWrite { address, length: N });
+
+            if self.rom_write_policy == RomWritePolicy::Fault {
+                return;
+            }
         }
 
-        let [lower, upper] = word.to_le_bytes();
+        self.check_watchpoints(address, N, true);
+
+        let ram = Rc::make_mut(&mut self.ram);
 
-        self.ram[address] = lower;
-        self.ram[address + 1] = upper;
+        for (i, byte) in bytes.into_iter().enumerate() {
+            ram[wrap_address(address, i)] = byte;
+        }
     }
 
-    pub fn write_long(&mut self, address: u16, word: u32) {
-        let address = address as usize;
+    fn check_watchpoints(&self, address: u16, length: usize, write: bool) {
+        for (index, watchpoint) in self.watchpoints.iter().enumerate() {
+            if (0..length).any(|i| watchpoint.matches(wrap_address(address, i), write)) {
+                self.events
+                    .borrow_mut()
+                    .push(MemoryEvent::Watchpoint(WatchpointHit {
+                        index,
+                        address,
+                        length,
+                        write,
+                    }));
+            }
+        }
+    }
+
+    /// Returns whether an access of `length` bytes at `address` should be performed, recording an
+    /// event if it is the first that should not
+    fn check_bounds(&self, address: u16, length: usize) -> bool {
+        if self.is_out_of_bounds() {
+            return false;
+        }
 
-        if address < self.rom_size {
-            println!("ERROR: Clobbering ROM data");
+        if address as usize + length <= MEMORY_SIZE || self.bounds_policy == BoundsPolicy::Wrap {
+            return true;
         }
 
-        let [lower_a, upper_a, lower_b, upper_b] = word.to_le_bytes();
+        self.events
+            .borrow_mut()
+            .push(MemoryEvent::OutOfBounds { address, length });
 
-        self.ram[address] = lower_a;
-        self.ram[address + 1] = upper_a;
-        self.ram[address + 2] = lower_b;
-        self.ram[address + 3] = upper_b;
+        false
     }
 }
+
+fn wrap_address(address: u16, offset: usize) -> usize {
+    (address as usize + offset) % MEMORY_SIZE
+}
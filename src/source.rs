@@ -0,0 +1,161 @@
+use std::{fs, path::Path};
+
+use crate::{
+    assembler::{split_operands, split_statements, unescape},
+    disasm::decode,
+    symbols::{Symbol, SymbolKind, Symbols},
+};
+
+/// A statement in the source that produced bytes in the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLine {
+    pub address: u16,
+    /// The line number, starting at 1
+    pub line: usize,
+}
+
+/// Maps addresses in the program back to the lines of the bass source it was assembled from
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    /// The file name of the source
+    pub name: String,
+    pub lines: Vec<String>,
+    /// The statements that produced bytes, in source order
+    pub entries: Vec<SourceLine>,
+    /// The labels, `constant`s, and `variable`s defined in the source
+    pub symbols: Symbols,
+}
+
+impl SourceMap {
+    pub fn load(path: &str, bytes: &[u8]) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("Could not read {path}: {err}"))?;
+
+        let mut source = SourceMap::parse(&text, bytes);
+        source.name = Path::new(path)
+            .file_name()
+            .map_or(path.into(), |name| name.to_string_lossy().into_owned());
+
+        Ok(source)
+    }
+
+    ///
+    /// Walks bass source, assigning each statement the address it was assembled to. Each
+    /// instruction advances the address by the size of the instruction decoded from `bytes` at
+    /// that address, and `db`, `dw`, `dl`, `fill`, and `origin` are sized from the source
+    ///
+    /// Definitions whose values aren't simple sums of numbers and earlier symbols are skipped
+    pub fn parse(text: &str, bytes: &[u8]) -> Self {
+        let mut source = SourceMap {
+            lines: text.lines().map(String::from).collect(),
+            ..Default::default()
+        };
+        let symbols = &mut source.symbols;
+        let mut address = 0u32;
+
+        for statement in split_statements(text) {
+            for name in statement.labels {
+                symbols.symbols.push(Symbol {
+                    name,
+                    value: address,
+                    kind: SymbolKind::Label,
+                });
+            }
+
+            let text = statement.text.as_str();
+
+            if text.is_empty() {
+                continue;
+            }
+
+            let (keyword, arguments) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            let arguments = arguments.trim();
+
+            let size = match keyword.to_lowercase().as_str() {
+                "constant" | "variable" => {
+                    let Some((name, value)) = arguments.split_once('=') else {
+                        continue;
+                    };
+
+                    if let Some(value) = symbols.evaluate(value) {
+                        symbols.symbols.push(Symbol {
+                            name: name.trim().to_string(),
+                            value,
+                            kind: SymbolKind::Constant,
+                        });
+                    }
+
+                    0
+                }
+                "origin" | "org" => {
+                    if let Some(value) = symbols.evaluate(arguments) {
+                        address = value;
+                    }
+
+                    0
+                }
+                "db" => data_size(arguments, 1),
+                "dw" => data_size(arguments, 2),
+                "dl" | "dd" => data_size(arguments, 4),
+                "fill" => {
+                    let length = arguments.split(',').next().unwrap_or("");
+
+                    symbols.evaluate(length).unwrap_or(0)
+                }
+                "architecture" | "output" | "include" | "define" | "base" | "endian" => 0,
+                _ => decode(bytes, address as u16).size() as u32,
+            };
+
+            if size > 0 {
+                source.entries.push(SourceLine {
+                    address: address as u16,
+                    line: statement.line,
+                });
+            }
+
+            address += size;
+        }
+
+        source
+    }
+
+    /// The line of the statement starting at `address`
+    pub fn line_at(&self, address: u16) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|entry| entry.address == address)
+            .map(|entry| entry.line)
+    }
+
+    /// The line of the statement containing `address`
+    pub fn line_of(&self, address: u16) -> Option<usize> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.address <= address)
+            .max_by_key(|entry| entry.address)
+            .map(|entry| entry.line)
+    }
+
+    /// The address of the first statement on or after `line`
+    pub fn address_of(&self, line: usize) -> Option<u16> {
+        self.entries
+            .iter()
+            .find(|entry| entry.line >= line)
+            .map(|entry| entry.address)
+    }
+}
+
+/// The size in bytes of a comma separated data list, where strings take one element per byte
+fn data_size(arguments: &str, element_size: u32) -> u32 {
+    split_operands(arguments)
+        .iter()
+        .map(|operand| {
+            let length = operand
+                .strip_prefix('"')
+                .and_then(|string| string.strip_suffix('"'))
+                .map_or(1, |string| unescape(string).map_or(0, |bytes| bytes.len()));
+
+            length as u32 * element_size
+        })
+        .sum()
+}
@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+use crate::source::SourceMap;
+
+/// Whether a symbol names a location in the program, or a plain value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Label,
+    Constant,
+}
+
+/// A named value, such as a label or constant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u32,
+    pub kind: SymbolKind,
+}
+
+/// Names for addresses and values in the loaded program
+#[derive(Clone, Debug, Default)]
+pub struct Symbols {
+    pub symbols: Vec<Symbol>,
+}
+
+impl Symbols {
+    ///
+    /// Loads a symbol table, or a `.asm` source file. Label addresses in source files are found
+    /// by decoding `bytes`, the program assembled from that source
+    pub fn load(path: &str, bytes: &[u8]) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("Could not read {path}: {err}"))?;
+
+        let is_source = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("asm"));
+
+        if is_source {
+            Ok(Symbols::parse_asm(&text, bytes))
+        } else {
+            Symbols::parse(&text)
+        }
+    }
+
+    ///
+    /// Parses a symbol table, with one hex value and name per line (such as `00000042 loop`).
+    /// Blank lines and lines starting with `;` or `#` are ignored
+    ///
+    /// Symbols are labels, unless they follow a `[constants]` section header, as written by bass.
+    /// A `[labels]` header switches back to labels
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut symbols = Vec::new();
+        let mut kind = SymbolKind::Label;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                kind = match section.trim() {
+                    "constants" => SymbolKind::Constant,
+                    _ => SymbolKind::Label,
+                };
+
+                continue;
+            }
+
+            let error = || format!("Invalid symbol on line {}: \"{line}\"", i + 1);
+
+            let (value, name) = line.split_once(char::is_whitespace).ok_or_else(error)?;
+            let value = value.strip_prefix("0x").unwrap_or(value);
+            let value = u32::from_str_radix(value, 16).map_err(|_| error())?;
+
+            symbols.push(Symbol {
+                name: name.trim().to_string(),
+                value,
+                kind,
+            });
+        }
+
+        Ok(Symbols { symbols })
+    }
+
+    ///
+    /// Collects the labels, `constant`s, and `variable`s defined in bass source. See
+    /// [`SourceMap::parse`] for how label addresses are found
+    pub fn parse_asm(text: &str, bytes: &[u8]) -> Self {
+        SourceMap::parse(text, bytes).symbols
+    }
+
+    /// The value of the symbol named `name`
+    pub fn lookup(&self, name: &str) -> Option<u32> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.name == name)
+            .map(|symbol| symbol.value)
+    }
+
+    /// The name of the first symbol with the value `value`
+    pub fn name_of(&self, value: u32) -> Option<&str> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.value == value)
+            .map(|symbol| symbol.name.as_str())
+    }
+
+    /// The name of the first label at `address`
+    pub fn label_at(&self, address: u16) -> Option<&str> {
+        self.labels()
+            .find(|symbol| symbol.value == address as u32)
+            .map(|symbol| symbol.name.as_str())
+    }
+
+    /// The closest label at or before `address`, with the offset from it, such as `loop+0x4`
+    pub fn describe(&self, address: u16) -> Option<String> {
+        let label = self
+            .labels()
+            .filter(|symbol| symbol.value <= address as u32)
+            .max_by_key(|symbol| symbol.value)?;
+
+        let offset = address as u32 - label.value;
+
+        Some(if offset == 0 {
+            label.name.clone()
+        } else {
+            format!("{}+{offset:#X}", label.name)
+        })
+    }
+
+    /// Formats `address` as hex, followed by its description if there is one
+    pub fn format_address(&self, address: u16) -> String {
+        match self.describe(address) {
+            Some(description) => format!("{address:#06X} <{description}>"),
+            None => format!("{address:#06X}"),
+        }
+    }
+
+    fn labels(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Label)
+    }
+
+    /// Evaluates a sum of numbers and symbol names, such as `rambuf+4`
+    pub(crate) fn evaluate(&self, expression: &str) -> Option<u32> {
+        let mut total = 0u32;
+        let mut term = String::new();
+        let mut negate = false;
+
+        for c in expression.trim().chars().chain(['+']) {
+            if c == '+' || c == '-' {
+                let value = self.evaluate_term(&term)?;
+
+                total = if negate {
+                    total.wrapping_sub(value)
+                } else {
+                    total.wrapping_add(value)
+                };
+
+                term.clear();
+                negate = c == '-';
+            } else {
+                term.push(c);
+            }
+        }
+
+        Some(total)
+    }
+
+    fn evaluate_term(&self, term: &str) -> Option<u32> {
+        let term = term.trim();
+
+        if let Some(hex) = term.strip_prefix("0x").or_else(|| term.strip_prefix('$')) {
+            u32::from_str_radix(hex, 16).ok()
+        } else if let Some(binary) = term.strip_prefix("0b").or_else(|| term.strip_prefix('%')) {
+            u32::from_str_radix(binary, 2).ok()
+        } else if term.starts_with(|c: char| c.is_ascii_digit()) {
+            term.parse().ok()
+        } else {
+            self.lookup(term)
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::clock::CYCLES_PER_MILLISECOND;
+
+/// The additional cycles spent per byte by instructions that move blocks of data
+pub const CYCLES_PER_TRANSFERRED_BYTE: u64 = 1;
+
+///
+/// The base cost in cycles of executing the instruction with prefix byte `inst_prefix_byte`
+///
+/// These are estimates, meant for comparing and budgeting programs rather than exact hardware timing
+pub fn instruction_cycles(inst_prefix_byte: u8) -> u64 {
+    match inst_prefix_byte {
+        // nop
+        0x00 => 1,
+        // ld.* Rx,(nnnn) | ld.* (nnnn),Rx
+        0x02..=0x07 => 3,
+        // ALU Rx,#16
+        0x08..=0x0F => 2,
+        // rset | crc
+        0x10 | 0x11 => 2,
+        // ALU Rx,#32
+        0x18..=0x1F => 3,
+        // Shifts
+        0x20..=0x27 => 1,
+        // ALU Rx,Ry
+        0x28..=0x2F => 1,
+        // ld.* Rx,(Ry) | ld.* (Ry),Rx
+        0x32..=0x37 => 2,
+        // mul
+        0x38 => 4,
+        // test | pmpw | pmpr | pmpbw | xfill | rfill
+        0x39..=0x3D | 0x3F => 4,
+        // div
+        0x3E => 16,
+        // printf | hex.* | dec.*
+        0x40 | 0x41 => 8,
+        // ret | push | pop
+        0x42..=0x44 => 2,
+        // err | exit | clc/sec | uivisible | gettime
+        0x45..=0x49 => 2,
+        // File and host operations
+        0x50..=0x5D => 8,
+        // jp
+        0x60..=0xAF => 2,
+        // call
+        0xB0..=0xFF => 3,
+        // Invalid instructions still consume a fetch
+        _ => 1,
+    }
+}
+
+/// The additional cost in cycles of moving `bytes` bytes
+pub fn transfer_cycles(bytes: u64) -> u64 {
+    bytes * CYCLES_PER_TRANSFERRED_BYTE
+}
+
+/// The estimated time on hardware in milliseconds to execute `cycles` cycles
+pub fn cycles_to_milliseconds(cycles: u64) -> f64 {
+    cycles as f64 / CYCLES_PER_MILLISECOND as f64
+}
@@ -0,0 +1,134 @@
+use ::tui::Frame;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+};
+
+use chip32_sim::{
+    breakpoint::Breakpoint,
+    cpu::CPU,
+    disasm::{disassemble_around, Condition, Instruction, Operand},
+    symbols::Symbols,
+};
+
+pub fn render_disassembly<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    state: &CPU,
+    breakpoints: &[Breakpoint],
+    symbols: &Symbols,
+    scroll: &mut isize,
+) {
+    // Remove 2 lines, one for top, one for bottom
+    let height = area.height.saturating_sub(2) as usize;
+    let extra = height + scroll.unsigned_abs();
+
+    let instructions = disassemble_around(state.ram.bytes(), state.pc, extra, extra);
+
+    let pc_index = instructions
+        .iter()
+        .position(|instruction| instruction.address == state.pc)
+        .unwrap_or(0);
+
+    // Don't scroll past the first or last instruction
+    let last_index = (instructions.len() as isize - 1).max(0);
+    let center = (pc_index as isize + *scroll).clamp(0, last_index);
+    *scroll = center - pc_index as isize;
+
+    // Keep the selected instruction centered, unless at the start or end of memory
+    let start = (center - height as isize / 2)
+        .min(instructions.len() as isize - height as isize)
+        .max(0) as usize;
+
+    let rows = instructions
+        .iter()
+        .skip(start)
+        .take(height)
+        .map(|instruction| {
+            let is_pc = instruction.address == state.pc;
+            let has_breakpoint = breakpoints
+                .iter()
+                .any(|breakpoint| breakpoint.address == instruction.address);
+
+            let marker = match (is_pc, has_breakpoint) {
+                (true, true) => ">*",
+                (true, false) => "> ",
+                (false, true) => " *",
+                (false, false) => "  ",
+            };
+
+            let words = instruction
+                .words
+                .iter()
+                .map(|word| format!("{word:04X}"))
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            let mut text = instruction.to_symbolic_string(symbols);
+
+            if is_pc {
+                if let Some(taken) = branch_taken(instruction, state) {
+                    text += if taken { "  (taken)" } else { "  (not taken)" };
+                }
+            }
+
+            let style = if is_pc {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if has_breakpoint {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+
+            let label = symbols
+                .label_at(instruction.address)
+                .map(|label| format!("{label}:"))
+                .unwrap_or_default();
+
+            Row::new([
+                Cell::from(marker),
+                Cell::from(format!("{:04X}", instruction.address)),
+                Cell::from(label),
+                Cell::from(words),
+                Cell::from(text),
+            ])
+            .style(style)
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(rows)
+        .widths(&[
+            Constraint::Length(2),
+            Constraint::Length(4),
+            Constraint::Length(12),
+            Constraint::Length(14),
+            Constraint::Min(10),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("Disassembly"));
+
+    f.render_widget(table, area);
+}
+
+/// Whether a conditional jump, call, or return will be taken with the current flags
+fn branch_taken(instruction: &Instruction, state: &CPU) -> Option<bool> {
+    if !matches!(instruction.mnemonic, "jp" | "call" | "ret") {
+        return None;
+    }
+
+    instruction
+        .operands
+        .iter()
+        .find_map(|operand| match operand {
+            Operand::Condition(condition) => Some(match condition {
+                Condition::NotZero => !state.zero,
+                Condition::Zero => state.zero,
+                Condition::NotCarry => !state.carry,
+                Condition::Carry => state.carry,
+            }),
+            _ => None,
+        })
+}
@@ -6,23 +6,42 @@ use tui::{
     widgets::{Block, Borders, Cell, List, ListItem, Row, Table, TableState},
 };
 
-use crate::tui::util::NamedCells;
-use chip32_sim::cpu::CPU;
+use crate::tui::{disassembly::render_disassembly, source::render_source, util::NamedCells};
+use chip32_sim::{
+    cpu::HaltState,
+    debugger::{is_return_address, Debugger},
+    timing::cycles_to_milliseconds,
+};
 
 pub fn render_main<B: Backend>(
     f: &mut Frame<B>,
     chunks: Vec<Rect>,
     table_state: &mut TableState,
-    state: &CPU,
-    next_state: &CPU,
+    debugger: &Debugger,
+    disassembly_scroll: &mut isize,
+    show_source: bool,
 ) {
+    let state = &debugger.state;
+    let next_state = &debugger.next_state;
+
     let side_chunks = Layout::default()
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .direction(Direction::Horizontal)
         .split(chunks[0]);
 
+    let register_chunks = Layout::default()
+        .constraints(
+            [
+                // 14 rows, plus one line for top, one for bottom
+                Constraint::Length(16),
+                Constraint::Min(3),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(side_chunks[0]);
+
     // Table
-    let placeholders = [Cell::from(""), Cell::from("")];
     let pc_row = Row::new(
         state.pc.named_cells("PC".into()).into_iter().chain(
             next_state
@@ -32,27 +51,27 @@ pub fn render_main<B: Backend>(
                 .into_iter(),
         ),
     );
+    let status = match &state.halt {
+        HaltState::Fault { pc, .. } => format!("{} at {pc:04X}", state.halt),
+        halt => halt.to_string(),
+    };
     let sp_row = Row::new(
         state
             .sp
             .named_cells("SP".into())
             .into_iter()
-            .chain(placeholders.clone().into_iter()),
+            .chain(status.named_cells("Status".into())),
     );
     let c_row = Row::new(
         state
             .carry
             .named_cells("Carry".into())
             .into_iter()
-            .chain(placeholders.clone().into_iter()),
-    );
-    let z_row = Row::new(
-        state
-            .zero
-            .named_cells("Zero".into())
-            .into_iter()
-            .chain(placeholders.clone().into_iter()),
+            .chain(state.cycles.to_string().named_cells("Cycles".into())),
     );
+    let z_row = Row::new(state.zero.named_cells("Zero".into()).into_iter().chain(
+        format!("{:.3}ms", cycles_to_milliseconds(state.cycles)).named_cells("Est. Time".into()),
+    ));
 
     let spacer_row = Row::new([
         Cell::from(""),
@@ -77,8 +96,19 @@ pub fn render_main<B: Backend>(
         )
     });
 
+    // Faults show the faulting instruction word and why it faulted in place of the spacer
+    let fault_row = match &state.halt {
+        HaltState::Fault { opcode, reason, .. } => Row::new(
+            opcode
+                .named_cells("Opcode".into())
+                .into_iter()
+                .chain(reason.clone().named_cells("Reason".into())),
+        ),
+        _ => spacer_row.clone(),
+    };
+
     let table = Table::new(
-        [pc_row, spacer_row.clone(), sp_row, c_row, z_row, spacer_row]
+        [pc_row, spacer_row, sp_row, c_row, z_row, fault_row]
             .into_iter()
             .chain(reg_rows),
     )
@@ -86,11 +116,44 @@ pub fn render_main<B: Backend>(
         Constraint::Length(10),
         Constraint::Length(16),
         Constraint::Length(10),
-        Constraint::Length(16),
+        // Fault reasons run as wide as the pane allows
+        Constraint::Min(16),
     ])
     .block(Block::default().borders(Borders::ALL).title("Registers"));
 
-    f.render_stateful_widget(table, side_chunks[0], table_state);
+    f.render_stateful_widget(table, register_chunks[0], table_state);
+
+    match &debugger.source {
+        Some(source) if show_source => render_source(
+            f,
+            register_chunks[1],
+            state,
+            source,
+            &debugger.breakpoints,
+            disassembly_scroll,
+        ),
+        _ => render_disassembly(
+            f,
+            register_chunks[1],
+            state,
+            &debugger.breakpoints,
+            &debugger.symbols,
+            disassembly_scroll,
+        ),
+    }
+
+    let log_chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Min(3),
+                // Add 2 lines, one for top, one for bottom
+                Constraint::Length(state.sp.clamp(1, 8) as u16 + 2),
+                Constraint::Length(state.ui.visibility.len().clamp(1, 8) as u16 + 2),
+            ]
+            .as_ref(),
+        )
+        .direction(Direction::Vertical)
+        .split(side_chunks[1]);
 
     // Log list
     let logs: Vec<ListItem> = state
@@ -98,12 +161,54 @@ pub fn render_main<B: Backend>(
         .iter()
         .rev()
         // Remove 2 lines, one for top, one for bottom
-        .take(side_chunks[1].height as usize - 2)
+        .take(log_chunks[0].height as usize - 2)
         .rev()
         .map(|l| ListItem::new(vec![Spans::from(Span::raw(l))]))
         .collect();
 
     let log_list = List::new(logs).block(Block::default().borders(Borders::ALL).title("Logs"));
 
-    f.render_widget(log_list, side_chunks[1]);
+    f.render_widget(log_list, log_chunks[0]);
+
+    // Stack, newest first. Values following a call instruction are shown as return addresses
+    let entries: Vec<ListItem> = state.stack[..state.sp]
+        .iter()
+        .rev()
+        .map(|value| {
+            if is_return_address(state, *value) {
+                ListItem::new(format!(
+                    "ret {}",
+                    debugger.symbols.format_address(*value as u16)
+                ))
+            } else {
+                ListItem::new(format!("{value:#010X}"))
+            }
+        })
+        .collect();
+
+    let stack_list = List::new(entries).block(Block::default().borders(Borders::ALL).title(
+        match debugger.symbols.describe(state.pc) {
+            Some(description) => format!("Stack (in {description})"),
+            None => "Stack".into(),
+        },
+    ));
+
+    f.render_widget(stack_list, log_chunks[1]);
+
+    // UI element visibility
+    let elements: Vec<ListItem> = state
+        .ui
+        .visibility
+        .iter()
+        .map(|(id, visible)| {
+            let visibility = if *visible { "Visible" } else { "Hidden" };
+
+            ListItem::new(format!("{id:#06X}: {visibility}"))
+        })
+        .collect();
+
+    let element_list =
+        List::new(elements).block(Block::default().borders(Borders::ALL).title("UI Elements"));
+
+    f.render_widget(element_list, log_chunks[2]);
 }
@@ -5,7 +5,7 @@ use tui::{
     widgets::{Block, Borders, Cell, Row, Table, TableState},
 };
 
-use chip32_sim::cpu::CPU;
+use chip32_sim::{cpu::CPU, mem::MEMORY_SIZE, symbols::Symbols};
 
 pub fn render_memory<B: Backend>(
     f: &mut Frame<B>,
@@ -13,6 +13,7 @@ pub fn render_memory<B: Backend>(
     address: u16,
     table_state: &mut TableState,
     state: &CPU,
+    symbols: &Symbols,
 ) {
     let header = (0..17).map(|i| {
         if i == 0 {
@@ -25,6 +26,8 @@ pub fn render_memory<B: Backend>(
 
     let widths = (0..17)
         .map(|i| Constraint::Length(if i == 0 { 8 } else { 2 }))
+        // Labels in the row
+        .chain([Constraint::Min(10)])
         .collect::<Vec<Constraint>>();
 
     let rows = (0..16).map(|i| {
@@ -35,13 +38,25 @@ pub fn render_memory<B: Backend>(
                 Cell::from(format!("{address:08X}"))
             } else {
                 let address = address + j - 1;
-                let data = state.ram.read_byte(address);
+
+                if address as usize >= MEMORY_SIZE {
+                    // Outside of memory
+                    return Cell::from("--");
+                }
+
+                // Read directly, as displaying memory must not trigger watchpoints
+                let data = state.ram.bytes()[address as usize];
 
                 Cell::from(format!("{data:02X}"))
             }
         });
 
-        Row::new(columns)
+        let labels = (address..address + 16)
+            .filter_map(|address| symbols.label_at(address))
+            .collect::<Vec<&str>>()
+            .join(", ");
+
+        Row::new(columns.chain([Cell::from(labels)]))
     });
 
     let table = Table::new(rows)
@@ -1,5 +1,5 @@
 use crossterm::event::{self, Event, KeyCode};
-use std::io;
+use std::{fmt::Display, io};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout},
@@ -10,7 +10,14 @@ use tui::{
 };
 use unicode_width::UnicodeWidthStr;
 
-use chip32_sim::cpu::{HaltState, CPU};
+use chip32_sim::{
+    breakpoint::{Breakpoint, Condition},
+    cpu::{HaltState, StepResult, CPU},
+    debugger::{Debugger, StopReason},
+    mem::Watchpoint,
+    source::SourceMap,
+    symbols::Symbols,
+};
 
 use self::{
     main::render_main,
@@ -18,22 +25,27 @@ use self::{
     modes::{App, DisplayMode},
 };
 
+mod disassembly;
 mod main;
 mod memory;
 pub(crate) mod modes;
+mod source;
 pub(crate) mod util;
 
+/// The maximum number of instructions executed by a single command
+const RUN_LIMIT: usize = 10000;
+
 pub fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     mut app: App,
-    mut state: CPU,
+    state: CPU,
+    symbols: Symbols,
+    source: Option<SourceMap>,
 ) -> io::Result<()> {
-    // Maintain two copies of the CPU state, one a step ahead
-    let mut next_state: CPU = state.clone();
-    next_state.step();
+    let mut debugger = Debugger::new(state, symbols, source);
 
     loop {
-        terminal.draw(|f| ui(f, &mut app, &state, &next_state))?;
+        terminal.draw(|f| ui(f, &mut app, &debugger))?;
 
         if let Event::Key(key) = event::read()? {
             if key.code == KeyCode::Esc {
@@ -55,37 +67,74 @@ pub fn run_app<B: Backend>(
 
             match key.code {
                 KeyCode::Enter => {
+                    // Follow the PC again after any command
+                    app.disassembly_scroll = 0;
+
                     match app.input.as_str() {
                         "s" | "step" => {
-                            // TODO: This is inefficient, but easy
-                            state = next_state.clone();
-                            next_state.step();
+                            app.message = String::new();
+
+                            if let StepResult::Watchpoint(hit) = debugger.step() {
+                                app.message =
+                                    stop_message(&debugger, StopReason::Watchpoint(hit.index));
+                            }
                         }
                         "r" | "run" => {
-                            let mut count = 0;
-
                             app.input = String::new();
 
-                            loop {
-                                let mut terminate = false;
-
-                                if let HaltState::Running = state.halt {
-                                    if count > 10000 {
-                                        terminate = true;
-                                    }
-
-                                    count += 1;
-                                } else {
-                                    terminate = true;
-                                }
+                            let reason = debugger.run(RUN_LIMIT);
+                            app.message = stop_message(&debugger, reason);
+                        }
+                        "n" | "next" => {
+                            let reason = debugger.step_over(RUN_LIMIT);
+                            app.message = stop_message(&debugger, reason);
+                        }
+                        "sl" | "line" => {
+                            app.message = match debugger.step_line(RUN_LIMIT) {
+                                Some(reason) => stop_message(&debugger, reason),
+                                None => "No source is loaded".into(),
+                            };
+                        }
+                        "src" => {
+                            app.show_source = !app.show_source;
+                            app.input = String::new();
+                        }
+                        "finish" => {
+                            app.message = match debugger.finish(RUN_LIMIT) {
+                                Some(reason) => stop_message(&debugger, reason),
+                                None => "Not in a subroutine".into(),
+                            };
+                            app.input = String::new();
+                        }
+                        "back" | "rs" => {
+                            app.message = match debugger.step_back() {
+                                Some(_) => format!(
+                                    "Stepped back to {}",
+                                    format_location(&debugger, debugger.state.pc)
+                                ),
+                                None => stop_message(&debugger, StopReason::HistoryStart),
+                            };
+                        }
+                        "rc" => {
+                            app.input = String::new();
 
-                                if terminate {
-                                    break;
-                                }
+                            let reason = debugger.reverse_run();
+                            app.message = stop_message(&debugger, reason);
+                        }
+                        "bl" => {
+                            let breakpoints = debugger
+                                .breakpoints
+                                .iter()
+                                .map(|breakpoint| format_breakpoint(&debugger, breakpoint))
+                                .collect::<Vec<String>>();
 
-                                state = next_state.clone();
-                                next_state.step();
-                            }
+                            app.message = list_message(&breakpoints, "breakpoints");
+                            app.input = String::new();
+                        }
+                        "wl" => {
+                            app.message =
+                                list_message(&debugger.state.ram.watchpoints, "watchpoints");
+                            app.input = String::new();
                         }
                         "m" => {
                             if let DisplayMode::Input(..) = app.display_mode {
@@ -104,7 +153,79 @@ pub fn run_app<B: Backend>(
                             return Ok(());
                         }
                         input => {
-                            if input.starts_with("m ") {
+                            if let Some(target) = input.strip_prefix("until ") {
+                                app.message = match debugger.parse_address(target) {
+                                    Ok(address) => {
+                                        let reason = debugger.run_to(RUN_LIMIT, address);
+
+                                        stop_message(&debugger, reason)
+                                    }
+                                    Err(err) => err,
+                                };
+                                app.input = String::new();
+                            } else if let Some(target) = input.strip_prefix("b ") {
+                                app.message = match debugger.parse_address(target) {
+                                    Ok(address) => add_breakpoint(&mut debugger, address, None),
+                                    Err(err) => err,
+                                };
+                                app.input = String::new();
+                            } else if let Some(arguments) = input.strip_prefix("bc ") {
+                                let (target, condition) =
+                                    arguments.trim().split_once(' ').unwrap_or((arguments, ""));
+
+                                app.message =
+                                    match debugger.parse_address(target).and_then(|address| {
+                                        Condition::parse(condition, &debugger.symbols)
+                                            .map(|condition| (address, condition))
+                                    }) {
+                                        Ok((address, condition)) => {
+                                            add_breakpoint(&mut debugger, address, Some(condition))
+                                        }
+                                        Err(err) => err,
+                                    };
+                                app.input = String::new();
+                            } else if let Some(index) = input.strip_prefix("bd ") {
+                                let breakpoints = &mut debugger.breakpoints;
+
+                                app.message = match index.trim().parse::<usize>() {
+                                    Ok(index) if index < breakpoints.len() => {
+                                        let breakpoint = breakpoints.remove(index);
+
+                                        format!(
+                                            "Deleted breakpoint {}",
+                                            format_breakpoint(&debugger, &breakpoint)
+                                        )
+                                    }
+                                    _ => format!("No breakpoint {index}"),
+                                };
+                                app.input = String::new();
+                            } else if let Some(spec) = input.strip_prefix("w ") {
+                                app.message = match spec.parse::<Watchpoint>() {
+                                    Ok(watchpoint) => {
+                                        let message = format!("Added watchpoint {watchpoint}");
+
+                                        debugger.state.ram.watchpoints.push(watchpoint);
+                                        debugger.refresh();
+
+                                        message
+                                    }
+                                    Err(err) => err,
+                                };
+                                app.input = String::new();
+                            } else if let Some(index) = input.strip_prefix("wd ") {
+                                let watchpoints = &mut debugger.state.ram.watchpoints;
+
+                                app.message = match index.trim().parse::<usize>() {
+                                    Ok(index) if index < watchpoints.len() => {
+                                        let watchpoint = watchpoints.remove(index);
+                                        debugger.refresh();
+
+                                        format!("Deleted watchpoint {watchpoint}")
+                                    }
+                                    _ => format!("No watchpoint {index}"),
+                                };
+                                app.input = String::new();
+                            } else if input.starts_with("m ") {
                                 let input = &input[2..];
                                 let address = u16::from_str_radix(input, 16).unwrap_or_else(|_| 0);
 
@@ -127,36 +248,38 @@ pub fn run_app<B: Backend>(
                 KeyCode::Backspace => {
                     app.input.pop();
                 }
-                KeyCode::Up => {
-                    if let DisplayMode::Memory {
+                KeyCode::Up => match app.display_mode {
+                    DisplayMode::Memory {
                         ref mut address,
                         state: _,
-                    } = app.display_mode
-                    {
+                    } => {
                         if *address >= 16 {
                             *address -= 16;
                         }
                     }
-                }
-                KeyCode::Down => {
-                    if let DisplayMode::Memory {
+                    DisplayMode::Input(..) => app.disassembly_scroll -= 1,
+                },
+                KeyCode::Down => match app.display_mode {
+                    DisplayMode::Memory {
                         ref mut address,
                         state: _,
-                    } = app.display_mode
-                    {
+                    } => {
                         if *address < 8 * 1024 - (16 * 16) {
                             // Don't scroll past last page
                             *address += 16;
                         }
                     }
-                }
+                    DisplayMode::Input(..) => app.disassembly_scroll += 1,
+                },
                 _ => {}
             }
         }
     }
 }
 
-fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App, state: &CPU, next_state: &CPU) {
+fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App, debugger: &Debugger) {
+    let state = &debugger.state;
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
@@ -165,22 +288,38 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App, state: &CPU, next_state: &CPU
                 Constraint::Percentage(76),
                 Constraint::Length(1),
                 Constraint::Length(3),
-                Constraint::Length(2),
+                Constraint::Length(5),
             ]
             .as_ref(),
         )
         .split(f.size());
 
     match app.display_mode {
-        DisplayMode::Input(ref mut table_state) => {
-            render_main(f, chunks.clone(), table_state, state, next_state)
-        }
+        DisplayMode::Input(ref mut table_state) => render_main(
+            f,
+            chunks.clone(),
+            table_state,
+            debugger,
+            &mut app.disassembly_scroll,
+            app.show_source,
+        ),
         DisplayMode::Memory {
             address,
             state: ref mut table_state,
-        } => render_memory(f, chunks.clone(), address, table_state, state),
+        } => render_memory(
+            f,
+            chunks.clone(),
+            address,
+            table_state,
+            state,
+            &debugger.symbols,
+        ),
     }
 
+    let message_paragraph =
+        Paragraph::new(app.message.as_ref()).style(Style::default().fg(Color::Cyan));
+    f.render_widget(message_paragraph, chunks[1]);
+
     let input_paragraph = Paragraph::new(app.input.as_ref())
         .style(Style::default().fg(Color::Yellow))
         .block(Block::default().borders(Borders::ALL).title("Input"));
@@ -206,9 +345,146 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App, state: &CPU, next_state: &CPU
             Span::raw(" to quit"),
             Span::raw("                    "),
             Span::styled("r", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" to run until program end"),
+            Span::raw(" to run until program end or breakpoint"),
+        ]),
+        Spans::from(vec![
+            Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to step over calls"),
+            Span::raw("    "),
+            Span::styled("sl", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to step a source line, "),
+            Span::styled("src", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to toggle source"),
+            Span::raw("    "),
+            Span::styled("finish", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to run until the subroutine returns"),
+            Span::raw("    "),
+            Span::styled(
+                "until [address|label]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to run to an address"),
+            Span::raw("    "),
+            Span::styled("rs", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to step back, "),
+            Span::styled("rc", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to run back to a breakpoint"),
+        ]),
+        Spans::from(vec![
+            Span::styled(
+                "b [address|label]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to add a breakpoint"),
+            Span::raw("    "),
+            Span::styled(
+                "bc [address|label] [condition]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to add a conditional breakpoint"),
+            Span::raw("    "),
+            Span::styled("bl", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to list, "),
+            Span::styled("bd [index]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to delete"),
+        ]),
+        Spans::from(vec![
+            Span::styled(
+                "w [r|w|rw:]start[-end]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to add a watchpoint"),
+            Span::raw("    "),
+            Span::styled("wl", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to list, "),
+            Span::styled("wd [index]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to delete"),
         ]),
     ]);
 
     f.render_widget(info_paragraph, chunks[3]);
 }
+
+fn add_breakpoint(debugger: &mut Debugger, address: u16, condition: Option<Condition>) -> String {
+    let breakpoint = Breakpoint { address, condition };
+    let message = format!(
+        "Added breakpoint {}: {}",
+        debugger.breakpoints.len(),
+        format_breakpoint(debugger, &breakpoint)
+    );
+
+    debugger.breakpoints.push(breakpoint);
+
+    message
+}
+
+/// Formats a breakpoint with the label and source line of its address
+fn format_breakpoint(debugger: &Debugger, breakpoint: &Breakpoint) -> String {
+    let mut text = format_location(debugger, breakpoint.address);
+
+    if let Some(condition) = &breakpoint.condition {
+        text += &format!(" if {condition}");
+    }
+
+    text
+}
+
+/// Formats an address with its label, and its source line if source is loaded
+fn format_location(debugger: &Debugger, address: u16) -> String {
+    let text = debugger.symbols.format_address(address);
+
+    match debugger
+        .source
+        .as_ref()
+        .and_then(|source| Some((source, source.line_of(address)?)))
+    {
+        Some((source, line)) => format!("{text} ({}:{line})", source.name),
+        None => text,
+    }
+}
+
+fn list_message<T: Display>(items: &[T], name: &str) -> String {
+    if items.is_empty() {
+        format!("No {name}")
+    } else {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| format!("{i}: {item}"))
+            .collect::<Vec<String>>()
+            .join("  ")
+    }
+}
+
+fn stop_message(debugger: &Debugger, reason: StopReason) -> String {
+    match reason {
+        StopReason::Halted => match &debugger.state.halt {
+            HaltState::Fault { pc, opcode, reason } => {
+                format!("Halted: Fault at {pc:#06X} ({opcode:#06X}): {reason}")
+            }
+            halt => format!("Halted: {halt}"),
+        },
+        StopReason::Breakpoint(index) => {
+            let breakpoint = &debugger.breakpoints[index];
+
+            format!(
+                "Stopped at breakpoint {index} ({})",
+                format_breakpoint(debugger, breakpoint)
+            )
+        }
+        StopReason::Watchpoint(index) => {
+            let watchpoint = &debugger.state.ram.watchpoints[index];
+
+            format!("Stopped by watchpoint {index} ({watchpoint})")
+        }
+        StopReason::Reached => format!(
+            "Stopped at {}",
+            format_location(debugger, debugger.state.pc)
+        ),
+        StopReason::Limit => format!("Stopped after {RUN_LIMIT} steps"),
+        StopReason::HistoryStart => format!(
+            "Stopped at the earliest saved state ({})",
+            format_location(debugger, debugger.state.pc)
+        ),
+    }
+}
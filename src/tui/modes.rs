@@ -10,6 +10,12 @@ pub struct App {
     /// Current value of the input box
     pub input: String,
     pub display_mode: DisplayMode,
+    /// Status shown above the input box, such as why execution stopped
+    pub message: String,
+    /// How many rows the disassembly or source is scrolled away from the PC
+    pub disassembly_scroll: isize,
+    /// Whether the loaded source is shown in place of the disassembly
+    pub show_source: bool,
 }
 
 impl Default for App {
@@ -17,6 +23,9 @@ impl Default for App {
         App {
             input: String::new(),
             display_mode: DisplayMode::Input(TableState::default()),
+            message: String::new(),
+            disassembly_scroll: 0,
+            show_source: true,
         }
     }
 }
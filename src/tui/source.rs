@@ -0,0 +1,91 @@
+use ::tui::Frame;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+};
+
+use chip32_sim::{breakpoint::Breakpoint, cpu::CPU, source::SourceMap};
+
+pub fn render_source<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    state: &CPU,
+    source: &SourceMap,
+    breakpoints: &[Breakpoint],
+    scroll: &mut isize,
+) {
+    // Remove 2 lines, one for top, one for bottom
+    let height = area.height.saturating_sub(2) as usize;
+
+    let current_line = source.line_of(state.pc);
+    let breakpoint_lines = breakpoints
+        .iter()
+        .filter_map(|breakpoint| source.line_at(breakpoint.address))
+        .collect::<Vec<usize>>();
+
+    // Line numbers start at 1
+    let current_index = current_line.map_or(0, |line| line - 1) as isize;
+
+    // Don't scroll past the first or last line
+    let last_index = (source.lines.len() as isize - 1).max(0);
+    let center = (current_index + *scroll).clamp(0, last_index);
+    *scroll = center - current_index;
+
+    // Keep the selected line centered, unless at the start or end of the file
+    let start = (center - height as isize / 2)
+        .min(source.lines.len() as isize - height as isize)
+        .max(0) as usize;
+
+    let rows = source
+        .lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(i, text)| {
+            let line = i + 1;
+            let is_current = current_line == Some(line);
+            let has_breakpoint = breakpoint_lines.contains(&line);
+
+            let marker = match (is_current, has_breakpoint) {
+                (true, true) => ">*",
+                (true, false) => "> ",
+                (false, true) => " *",
+                (false, false) => "  ",
+            };
+
+            let style = if is_current {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if has_breakpoint {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+
+            Row::new([
+                Cell::from(marker),
+                Cell::from(line.to_string()),
+                Cell::from(text.replace('\t', "    ")),
+            ])
+            .style(style)
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(rows)
+        .widths(&[
+            Constraint::Length(2),
+            Constraint::Length(5),
+            Constraint::Min(10),
+        ])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(source.name.as_str()),
+        );
+
+    f.render_widget(table, area);
+}
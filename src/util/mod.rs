@@ -1,3 +1,5 @@
 pub mod bitwise;
+pub mod crc;
 pub mod num;
+pub mod rng;
 pub mod serde;
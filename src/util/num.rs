@@ -1,3 +1,5 @@
+use std::num::ParseIntError;
+
 pub trait LowerWord {
     fn to_lower_word(self) -> u16;
 }
@@ -30,3 +32,11 @@ impl LowerLong for u64 {
             | (bytes[0] as u32)
     }
 }
+
+/// Parses a decimal or 0x prefixed hex number
+pub fn parse_number(value: &str) -> Result<u32, ParseIntError> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+}
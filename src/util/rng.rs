@@ -0,0 +1,41 @@
+/// Used when no seed is provided, or when the provided seed is 0 (xorshift cannot leave 0)
+const DEFAULT_SEED: u32 = 0x2545F491;
+
+///
+/// A small, deterministic xorshift32 generator, so that simulated random data is repeatable between runs
+///
+#[derive(Clone)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng {
+            state: if seed == 0 { DEFAULT_SEED } else { seed },
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut state = self.state;
+
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+
+        self.state = state;
+
+        state
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        // The upper bits are the best distributed
+        (self.next_u32() >> 24) as u8
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::new(DEFAULT_SEED)
+    }
+}
@@ -1,4 +1,4 @@
-use serde::{de::Visitor, Deserializer, __private::fmt};
+use serde::{__private::fmt, de::Visitor, Deserializer};
 
 trait HexStringOrInt {
     fn to_int(self) -> u32;
@@ -68,3 +68,10 @@ where
 
     deserializer.deserialize_any(HexOrInt)
 }
+
+pub fn serde_option_string_or_int<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    serde_string_or_int(deserializer).map(Some)
+}
@@ -0,0 +1,104 @@
+use std::{collections::HashMap, fs};
+
+use chip32_sim::{apf::parse_json, cpu::CPU};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_adjusts_file_offset() {
+    let cpu = test_adjust("adjfo", "0x21", "0x10");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_2.bin").unwrap();
+    assert_eq!(
+        cpu.bridge.read_bytes(0x12345678, image.len() - 0x10),
+        image[0x10..]
+    );
+    assert_eq!(
+        cpu.bridge.read_byte(0x12345678 + image.len() as u32 - 0x10),
+        0
+    );
+}
+
+#[test]
+fn it_adjusts_file_size() {
+    let cpu = test_adjust("adjfs", "0x21", "0x20");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_2.bin").unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x12345678, 0x20), image[..0x20]);
+    assert_eq!(cpu.bridge.allocated_size(), 0x1000);
+
+    // Shrinking below size_maximum allows the load
+    let cpu = test_adjust("adjfs", "0x22", "0x1000");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_3.bin").unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x100000, 0x1000), image[..0x1000]);
+}
+
+#[test]
+fn it_adjusts_load_pointer() {
+    let cpu = test_adjust("adjlp", "0x21", "0x300000");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_2.bin").unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x300000, image.len()), image);
+    assert_eq!(cpu.bridge.read_byte(0x12345678), 0);
+
+    // Provides an address for slots without one
+    let cpu = test_adjust("adjlp", "0x20", "0x300000");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_1.bin").unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x300000, image.len()), image);
+}
+
+#[test]
+fn it_adjust_should_fail() {
+    // Offset past the end of the file
+    let cpu = test_adjust("adjfo", "0x21", "0x10000000");
+    assert!(!cpu.zero);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    // No slot
+    let cpu = test_adjust_without_load("adjfs", "0x99", "0x10");
+    assert!(!cpu.zero);
+    assert!(cpu.file_state.adjustments.is_empty());
+}
+
+#[test]
+fn it_keeps_adjustments_per_slot() {
+    let cpu = test_adjust_without_load("adjfs", "0x21", "0x10");
+    assert!(cpu.zero);
+
+    let adjustment = &cpu.file_state.adjustments[&0x21];
+    assert_eq!(adjustment.size, Some(0x10));
+    assert_eq!(adjustment.offset, None);
+    assert_eq!(adjustment.load_pointer, None);
+    assert!(!cpu.file_state.adjustments.contains_key(&0x22));
+}
+
+fn test_adjust(command: &str, slot: &str, value: &str) -> CPU {
+    test_adjust_with_steps(command, slot, value, 4)
+}
+
+fn test_adjust_without_load(command: &str, slot: &str, value: &str) -> CPU {
+    test_adjust_with_steps(command, slot, value, 3)
+}
+
+fn test_adjust_with_steps(command: &str, slot: &str, value: &str, steps: u32) -> CPU {
+    test_command(
+        "tests/asm/adjust.asm",
+        "tests/bin/adjust.bin",
+        HashMap::from([("command", command), ("slot", slot), ("value", value)]),
+        steps,
+        |cpu| {
+            cpu.file_state.slots = parse_json("tests/data.json");
+            // Start from success so failures are observable
+            cpu.zero = true;
+        },
+        |_| {},
+    )
+}
@@ -84,6 +84,24 @@ fn it_alu_shift() {
     test_alu_with_target("ror", "r1,#2", 0x10000000, 0, 0x04000000, false, false);
 }
 
+#[test]
+fn it_alu_rset() {
+    test_alu_with_target("rset", "r1,r2", 0xDEADBEEF, 0, 0xDEADBEEE, false, true);
+    test_alu_with_target("rset", "r1,r2", 0xDEADBEEF, 31, 0x5EADBEEF, false, true);
+
+    // Bit already clear
+    test_alu_with_target("rset", "r1,r2", 0xDEADBEEF, 4, 0xDEADBEEF, false, false);
+
+    // Only the lower 5 bits of the index are used
+    test_alu_with_target("rset", "r1,r2", 0xDEADBEEF, 32, 0xDEADBEEE, false, true);
+    test_alu_with_target(
+        "rset", "r1,r2", 0xDEADBEEF, 0xFFFFFFFF, 0x5EADBEEF, false, true,
+    );
+
+    // Should clear to 0
+    test_alu_with_target("rset", "r1,r2", 0x80000000, 31, 0x0, true, true);
+}
+
 #[test]
 fn it_alu_logic_double_reg() {
     test_alu_with_target(
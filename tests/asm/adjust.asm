@@ -0,0 +1,10 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+ld r1,#{slot}
+ld r2,#{value}
+{command} r1,r2
+loadf r1
@@ -0,0 +1,12 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+ld r1,#data
+ld r2,#{length}
+crc r1,r2
+
+data:
+db "{string}",0
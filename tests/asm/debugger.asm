@@ -0,0 +1,33 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+jp error
+
+// Init vector (0x2)
+ld r1,#0x1B00
+ld r2,#0x1234
+store:
+ld.w (r1),r2
+print:
+hex.w r2
+cmp r2,#0
+skipped:
+call z,subroutine
+called:
+call subroutine
+after:
+exit 0
+
+// Calls leaf, which runs {leaf}
+subroutine:
+add r2,#1
+call leaf
+subroutine_return:
+ret
+
+leaf:
+{leaf}
+ret
+
+error:
+exit 1
@@ -0,0 +1,35 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+ld r1,#0x20
+ld r2,#0x12345678
+ld.b r3,(0x1B00)
+ld.w (0x1B02),r4
+ld.l r5,(r6)
+ld.l (r7),r8
+add r1,r2
+cmp r3,#5
+asl r1,#4
+ror r2,r3
+mul r1,r2
+hex.w r9
+dec.l r10
+printf r2
+push r3
+pop r4
+sec
+clc
+ret nz
+ret
+uivisible r1,r0
+host r0,r1
+close
+queryslot r2
+target:
+jp target
+jp c,target
+call z,target
+exit 1
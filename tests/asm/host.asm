@@ -0,0 +1,9 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+ld r1,#{command}
+ld r2,#{parameter}
+host r1,r2
@@ -0,0 +1,8 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+dw {word}
+nop
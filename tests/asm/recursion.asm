@@ -0,0 +1,20 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+jp error
+
+// Init vector (0x2)
+ld r1,#{depth}
+call recurse
+exit 0
+
+// Recurses r1 times, keeping r1 on the stack at each level
+recurse:
+push r1
+sub r1,#1
+call nz,recurse
+pop r1
+ret
+
+error:
+exit 1
@@ -0,0 +1,12 @@
+architecture chip32.vm
+
+// Error vector (0x0)
+nop
+
+// Init vector (0x2)
+ld r1,#{element}
+ld r2,#{visible}
+uivisible r1,r2
+ld r1,#0x20
+ld r2,#1
+uivisible r1,r2
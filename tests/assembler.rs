@@ -0,0 +1,92 @@
+use std::fs;
+
+use chip32_sim::{assembler::assemble, disasm::disassemble};
+
+fn listing(bytes: &[u8]) -> Vec<String> {
+    disassemble(bytes, 0, bytes.len() as u16)
+        .iter()
+        .map(|instruction| instruction.to_string())
+        .collect()
+}
+
+#[test]
+fn it_matches_bass_on_the_example() {
+    let source = fs::read_to_string("example/example_chip32.asm").unwrap();
+    let expected = fs::read("example/example_chip32.bin").unwrap();
+
+    assert_eq!(assemble(&source).unwrap(), expected);
+}
+
+#[test]
+fn it_resolves_forward_references() {
+    let bytes = assemble(
+        "
+        jp end
+        ld r1,#big
+        constant big = 0x12345678
+        end: exit 0
+        ",
+    )
+    .unwrap();
+
+    // The 32 bit immediate moves end to 0x8
+    assert_eq!(
+        listing(&bytes),
+        vec!["jp 0x0008", "ld R1,#0x12345678", "exit 0"]
+    );
+}
+
+#[test]
+fn it_assembles_data() {
+    let bytes = assemble(
+        r#"
+        origin 0x4
+        data: db "Hi\n",0xFF
+        dw 0x1234
+        dl data+1
+        fill 2, 0xAA
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        bytes,
+        vec![0, 0, 0, 0, b'H', b'i', b'\n', 0xFF, 0x34, 0x12, 0x05, 0, 0, 0, 0xAA, 0xAA]
+    );
+}
+
+#[test]
+fn it_evaluates_expressions() {
+    let bytes = assemble(
+        "
+        constant base = 0x1B00
+        ld r1,#(base + 4) * 2 - %10
+        ld.w r2,(base | 0x2)
+        cmp r3,#'A'
+        ",
+    )
+    .unwrap();
+
+    assert_eq!(
+        listing(&bytes),
+        vec!["ld R1,#0x3606", "ld.w R2,(0x1B02)", "cmp R3,#0x41"]
+    );
+}
+
+#[test]
+fn it_reports_errors_with_line_numbers() {
+    assert_eq!(
+        assemble("nop\nfoo r1").unwrap_err(),
+        "Line 2: Unknown instruction \"foo\""
+    );
+    assert_eq!(
+        assemble("jp missing").unwrap_err(),
+        "Line 1: Unknown symbol \"missing\""
+    );
+    assert_eq!(
+        assemble("asl r1,#17").unwrap_err(),
+        "Line 1: Shift amount 17 must be from 1 to 16"
+    );
+    assert!(assemble("ld r1,(r2)").is_err());
+    assert!(assemble("a: nop\na: nop").is_err());
+}
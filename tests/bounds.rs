@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use chip32_sim::{
+    apf::DataSlot,
+    cpu::{FileLoadedState, HaltState, CPU},
+    mem::BoundsPolicy,
+};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_wraps_reads() {
+    let cpu = test_bounds("ld.w r1,(r2)", "0x1FFF", BoundsPolicy::Wrap);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    // The error vector nop is the first word of memory
+    assert_eq!(cpu.work_regs[1], 0xBE);
+
+    let cpu = test_bounds("ld.l r1,(r2)", "0x1FFE", BoundsPolicy::Wrap);
+    assert_eq!(cpu.work_regs[1], 0xBEEF);
+}
+
+#[test]
+fn it_wraps_writes() {
+    let cpu = test_bounds("ld.l (r2),r1", "0x1FFE", BoundsPolicy::Wrap);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.ram.read_word(0x1FFE), 0xBEEF);
+    assert_eq!(cpu.ram.read_word(0), 0xDEAD);
+}
+
+#[test]
+fn it_faults_on_out_of_bounds_access() {
+    for (command, address) in [
+        ("ld.w r1,(r2)", "0x1FFF"),
+        ("ld.l r1,(r2)", "0x1FFE"),
+        ("ld.b r1,(r2)", "0x2000"),
+        ("ld.l (r2),r1", "0x1FFD"),
+        ("ld.w r1,(r2)", "0xFFFF"),
+    ] {
+        let cpu = test_bounds(command, address, BoundsPolicy::Fault);
+
+        match cpu.halt {
+            HaltState::Fault { pc, .. } => assert_eq!(pc, 0x6, "{command} {address}"),
+            _ => panic!("{command} {address} did not fault"),
+        }
+
+        // Writes are not performed, and reads don't load a value
+        assert_eq!(cpu.ram.read_word(0x1FFE), 0xBEEF);
+        assert_eq!(cpu.work_regs[1], 0xDEADBEEF);
+        assert!(!cpu.zero);
+    }
+
+    // In bounds accesses at the end of memory succeed
+    let cpu = test_bounds("ld.w r1,(r2)", "0x1FFE", BoundsPolicy::Fault);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.work_regs[1], 0xBEEF);
+}
+
+#[test]
+fn it_jumps_to_error_on_out_of_bounds_access() {
+    let cpu = test_bounds("ld.w r1,(r2)", "0x1FFF", BoundsPolicy::ErrorJump);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.error_pc_reg, 0x8);
+    // The load was abandoned
+    assert_eq!(cpu.work_regs[1], 0xDEADBEEF);
+    assert!(!cpu.zero);
+}
+
+#[test]
+fn it_faults_on_immediate_past_end_of_memory() {
+    // ld r1,#nnnn, with the immediate past the end of memory
+    let cpu = test_immediate(0x0801, BoundsPolicy::Fault);
+    match cpu.halt {
+        HaltState::Fault { pc, ref reason, .. } => {
+            assert_eq!(pc, 0x1FFE);
+            assert!(reason.contains("outside of memory"), "{reason}");
+        }
+        _ => panic!("Reading an immediate past the end of memory did not fault"),
+    }
+    assert_eq!(cpu.work_regs[1], 0xDEADBEEF);
+    assert!(!cpu.zero);
+
+    // ld.w r1,(nnnn)
+    let cpu = test_immediate(0x0401, BoundsPolicy::ErrorJump);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.work_regs[1], 0xDEADBEEF);
+}
+
+#[test]
+fn it_handles_pc_past_end_of_memory() {
+    let cpu = test_pc(BoundsPolicy::Wrap);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    // Wrapped around to execute the error vector
+    assert_eq!(cpu.pc, 0x2);
+
+    // Overrunning memory is a failure, not a fault, as nothing was fetched
+    let cpu = test_pc(BoundsPolicy::Fault);
+    assert!(matches!(cpu.halt, HaltState::Failure));
+    assert_eq!(cpu.pc, 0x2000);
+
+    let cpu = test_pc(BoundsPolicy::ErrorJump);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.error_pc_reg, 0x2000);
+}
+
+#[test]
+fn it_stops_multi_byte_transfers_at_the_first_bad_access() {
+    for command in ["read r2,r3", "getname r3,r2"] {
+        let cpu = test_transfer(command, BoundsPolicy::ErrorJump);
+        assert!(matches!(cpu.halt, HaltState::Running), "{command}");
+        assert_eq!(cpu.pc, 0, "{command}");
+        assert_eq!(cpu.error_pc_reg, 0x8, "{command}");
+        assert!(!cpu.zero, "{command}");
+
+        let bounds_logs = cpu
+            .logs
+            .iter()
+            .filter(|log| log.contains("outside of memory"))
+            .count();
+        assert_eq!(bounds_logs, 1, "{command}");
+
+        // Bytes before the end of memory are written
+        assert_eq!(cpu.ram.read_byte(0x1FFF), b'a', "{command}");
+
+        let cpu = test_transfer(command, BoundsPolicy::Fault);
+        match cpu.halt {
+            HaltState::Fault { pc, ref reason, .. } => {
+                assert_eq!(pc, 0x6, "{command}");
+                assert!(reason.contains("0x2000"), "{reason}");
+            }
+            _ => panic!("{command} did not fault"),
+        }
+        assert!(!cpu.zero, "{command}");
+    }
+}
+
+fn test_bounds(command: &str, address: &str, policy: BoundsPolicy) -> CPU {
+    test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/bounds.bin",
+        HashMap::from([("command", command), ("address", address)]),
+        2,
+        |cpu| {
+            cpu.ram.bounds_policy = policy;
+            cpu.ram.write_word(0x1FFE, 0xBEEF);
+            cpu.work_regs[1] = 0xDEADBEEF;
+        },
+        |_| {},
+    )
+}
+
+/// Executes the nops at the end of memory, then continues past the end
+fn test_pc(policy: BoundsPolicy) -> CPU {
+    test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/bounds.bin",
+        HashMap::from([("command", "nop"), ("address", "0")]),
+        3,
+        |cpu| {
+            cpu.ram.bounds_policy = policy;
+            cpu.pc = 0x1FFC;
+        },
+        |_| {},
+    )
+}
+
+/// Executes `opcode` from the last word of memory
+fn test_immediate(opcode: u16, policy: BoundsPolicy) -> CPU {
+    test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/bounds.bin",
+        HashMap::from([("command", "nop"), ("address", "0")]),
+        1,
+        |cpu| {
+            cpu.ram.bounds_policy = policy;
+            cpu.ram.write_word(0x1FFE, opcode);
+            cpu.pc = 0x1FFE;
+            cpu.work_regs[1] = 0xDEADBEEF;
+        },
+        |_| {},
+    )
+}
+
+/// Executes `command` with R2 pointing at a 0x20 byte run of `a`s that crosses the end of memory
+fn test_transfer(command: &str, policy: BoundsPolicy) -> CPU {
+    let name = "a".repeat(0x1F);
+
+    test_command(
+        "tests/asm/bounds.asm",
+        &format!("tests/bin/{}.bin", command.replace([' ', ','], "_")),
+        HashMap::from([("command", command), ("address", "0x1FF0")]),
+        2,
+        |cpu| {
+            cpu.ram.bounds_policy = policy;
+            cpu.work_regs[3] = 0x20;
+
+            // read copies from the open file, and getname from the slot's file name
+            cpu.file_state.loaded = FileLoadedState::Loaded {
+                slot: 0x20,
+                data: vec![b'a'; 0x20].into(),
+                offset: 0,
+            };
+            cpu.file_state.slots.push(DataSlot {
+                id: 0x20,
+                filename: name,
+                address: None,
+                size_maximum: None,
+                deferload: false,
+            });
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use chip32_sim::{
+    breakpoint::{Breakpoint, Condition},
+    cpu::CPU,
+    symbols::Symbols,
+};
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_evaluates_conditions() {
+    let mut cpu = load_cpu();
+    cpu.work_regs[3] = 0x20;
+    cpu.zero = true;
+
+    assert!(evaluate("r3 == 0x20 && z", &cpu));
+    assert!(evaluate("r3 == 32", &cpu));
+    assert!(!evaluate("r3 == 0x20 && c", &cpu));
+    assert!(evaluate("r3 == 0x20 && !c", &cpu));
+    assert!(evaluate("r3 != 0x20 || z", &cpu));
+    assert!(evaluate(
+        "r3 > 0x1F && r3 >= 0x20 && r3 < 0x21 && r3 <= 0x20",
+        &cpu
+    ));
+    assert!(evaluate("!(r3 == 0x20 && c)", &cpu));
+    assert!(evaluate("R3", &cpu));
+    assert!(!evaluate("r4", &cpu));
+    assert!(evaluate("pc == 0x2 && sp == 0", &cpu));
+}
+
+#[test]
+fn it_resolves_symbols_in_conditions() {
+    let mut cpu = load_cpu();
+    cpu.work_regs[1] = 0x1B00;
+
+    let symbols = Symbols::parse("; Symbols\n00001B00 rambuf\n0x42 loop\n").unwrap();
+    assert_eq!(symbols.lookup("rambuf"), Some(0x1B00));
+    assert_eq!(symbols.lookup("loop"), Some(0x42));
+    assert_eq!(symbols.lookup("missing"), None);
+
+    let condition = Condition::parse("r1 == rambuf", &symbols).unwrap();
+    assert!(condition.evaluate(&cpu));
+}
+
+#[test]
+fn it_rejects_invalid_conditions() {
+    let symbols = Symbols::default();
+
+    assert!(Condition::parse("", &symbols).is_err());
+    assert!(Condition::parse("r3 ==", &symbols).is_err());
+    assert!(Condition::parse("r16 == 1", &symbols).is_err());
+    assert!(Condition::parse("(z", &symbols).is_err());
+    assert!(Condition::parse("z c", &symbols).is_err());
+    assert!(Condition::parse("r1 + 1", &symbols).is_err());
+    assert!(Condition::parse("unknown == 1", &symbols).is_err());
+}
+
+#[test]
+fn it_hits_breakpoints() {
+    let mut cpu = load_cpu();
+
+    let breakpoint = Breakpoint {
+        address: 0x2,
+        condition: None,
+    };
+    assert!(breakpoint.is_hit(&cpu));
+    assert_eq!(breakpoint.to_string(), "0x0002");
+
+    let breakpoint = Breakpoint {
+        address: 0x2,
+        condition: Some(Condition::parse("r1 == 5", &Symbols::default()).unwrap()),
+    };
+    assert!(!breakpoint.is_hit(&cpu));
+    assert_eq!(breakpoint.to_string(), "0x0002 if r1 == 5");
+
+    cpu.work_regs[1] = 5;
+    assert!(breakpoint.is_hit(&cpu));
+
+    // Different address
+    cpu.pc = 0x4;
+    assert!(!breakpoint.is_hit(&cpu));
+}
+
+fn evaluate(condition: &str, cpu: &CPU) -> bool {
+    Condition::parse(condition, &Symbols::default())
+        .unwrap()
+        .evaluate(cpu)
+}
+
+fn load_cpu() -> CPU {
+    test_command_without_setup(
+        "tests/asm/gettime.asm",
+        "tests/bin/breakpoint.bin",
+        HashMap::new(),
+        0,
+        |_| {},
+    )
+}
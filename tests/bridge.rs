@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use chip32_sim::cpu::CPU;
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_writes_and_reads_bridge() {
+    let cpu = test_bridge("pmpw", "0x51000000", "0x123456FF", 0x123456FF);
+    // Bridge is big endian
+    assert_eq!(cpu.bridge.read_byte(0x51000000), 0x12);
+    assert_eq!(cpu.bridge.read_byte(0x51000003), 0xFF);
+
+    let cpu = test_bridge("pmpbw", "0x51000000", "0x123456FF", 0xFF563412);
+    assert_eq!(cpu.bridge.read_byte(0x51000000), 0xFF);
+    assert_eq!(cpu.bridge.read_byte(0x51000003), 0x12);
+}
+
+#[test]
+fn it_reads_unwritten_bridge_as_zero() {
+    let cpu = test_command_without_setup(
+        "tests/asm/bridge.asm",
+        "tests/bin/pmpr.bin",
+        HashMap::from([
+            ("command", "ld"),
+            ("address", "0x1000"),
+            ("value", "0xDEADBEEF"),
+        ]),
+        4,
+        |cpu| {
+            assert_eq!(cpu.work_regs[3], 0);
+        },
+    );
+
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+}
+
+#[test]
+fn it_writes_across_pages() {
+    test_bridge("pmpw", "0xFFE", "0xCADEDEAD", 0xCADEDEAD);
+    // Wraps at the end of the address space
+    let cpu = test_bridge("pmpw", "0xFFFFFFFE", "0xCADEDEAD", 0xCADEDEAD);
+    assert_eq!(cpu.bridge.read_byte(0x0), 0xDE);
+}
+
+fn test_bridge(command: &str, address: &str, value: &str, result: u32) -> CPU {
+    test_command_without_setup(
+        "tests/asm/bridge.asm",
+        &format!("tests/bin/{command}.bin"),
+        HashMap::from([("command", command), ("address", address), ("value", value)]),
+        4,
+        |cpu| {
+            assert_eq!(cpu.work_regs[3], result, "R3");
+            assert_eq!(cpu.bridge.read_long(cpu.work_regs[1]), result);
+        },
+    )
+}
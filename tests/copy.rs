@@ -0,0 +1,133 @@
+use std::{collections::HashMap, fs};
+
+use chip32_sim::{
+    apf::parse_json,
+    cpu::{FileLoadedState, CPU},
+};
+use util::{test_command, test_command_without_setup};
+
+mod util;
+
+const IMAGE_PATH: &str = "example/ex_image_1.bin";
+
+#[test]
+fn it_copies_whole_file() {
+    let cpu = test_copy("0x20", "0", "0x100000", "184320");
+    assert!(cpu.zero);
+
+    let image = fs::read(IMAGE_PATH).unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x100000, image.len()), image);
+    assert_eq!(cpu.bridge.read_byte(0x100000 + image.len() as u32), 0);
+}
+
+#[test]
+fn it_copies_from_offset() {
+    let cpu = test_copy("0x20", "0xB400", "0x0B400", "46080");
+    assert!(cpu.zero);
+
+    let image = fs::read(IMAGE_PATH).unwrap();
+    assert_eq!(
+        cpu.bridge.read_bytes(0xB400, 46080),
+        image[0xB400..0xB400 + 46080]
+    );
+    assert_eq!(cpu.bridge.read_byte(0xB3FF), 0);
+
+    // Copy advances the file offset
+    if let FileLoadedState::Loaded { offset, .. } = cpu.file_state.loaded {
+        assert_eq!(offset, 0xB400 + 46080);
+    } else {
+        panic!("File was not open");
+    }
+}
+
+#[test]
+fn it_copies_from_adjusted_file() {
+    // Seeks are relative to the adjusted offset
+    let cpu = test_copy_with_setup("0x20", "0x10", "0x100000", "0x100", |cpu| {
+        cpu.file_state.adjustments.entry(0x20).or_default().offset = Some(0xB400);
+    });
+    assert!(cpu.zero);
+
+    let image = fs::read(IMAGE_PATH).unwrap();
+    assert_eq!(
+        cpu.bridge.read_bytes(0x100000, 0x100),
+        image[0xB410..0xB510]
+    );
+
+    // The adjusted size is the end of the file
+    let cpu = test_copy_with_setup("0x20", "0x80", "0x100000", "0x100", |cpu| {
+        cpu.file_state.adjustments.entry(0x20).or_default().size = Some(0x100);
+    });
+    assert!(!cpu.zero);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+}
+
+#[test]
+fn it_copy_should_not_pass_eof() {
+    let cpu = test_copy("0x20", "0x2C000", "0x0", "0x2000");
+    assert!(!cpu.zero);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+}
+
+#[test]
+fn it_copy_should_not_pass_end_of_bridge() {
+    let cpu = test_copy("0x20", "0", "0xFFFFFF00", "0x200");
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    // The file offset is not advanced
+    if let FileLoadedState::Loaded { offset, .. } = cpu.file_state.loaded {
+        assert_eq!(offset, 0);
+    } else {
+        panic!("File was not open");
+    }
+
+    // Up to the end of the bridge is allowed
+    let cpu = test_copy("0x20", "0", "0xFFFFFF00", "0x100");
+    assert!(cpu.zero);
+    assert_eq!(cpu.bridge.read_byte(0x0), 0);
+}
+
+#[test]
+fn it_copy_requires_open_file() {
+    test_command_without_setup(
+        "tests/asm/fill.asm",
+        "tests/bin/copy.bin",
+        HashMap::from([("command", "copy"), ("address", "0x0"), ("value", "0x10")]),
+        3,
+        |cpu| {
+            // Jump to error
+            assert_eq!(cpu.pc, 0);
+            assert_eq!(cpu.bridge.allocated_size(), 0);
+        },
+    );
+}
+
+fn test_copy(slot: &str, seek: &str, address: &str, length: &str) -> CPU {
+    test_copy_with_setup(slot, seek, address, length, |_| {})
+}
+
+fn test_copy_with_setup<T: FnOnce(&mut CPU)>(
+    slot: &str,
+    seek: &str,
+    address: &str,
+    length: &str,
+    setup: T,
+) -> CPU {
+    test_command(
+        "tests/asm/copy.asm",
+        "tests/bin/copy.bin",
+        HashMap::from([
+            ("slot", slot),
+            ("seek", seek),
+            ("address", address),
+            ("length", length),
+        ]),
+        7,
+        |cpu| {
+            cpu.file_state.slots = parse_json("tests/data.json");
+            setup(cpu);
+        },
+        |_| {},
+    )
+}
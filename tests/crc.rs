@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use chip32_sim::{cpu::CPU, util::crc::crc32};
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_computes_crc32() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"a"), 0xE8B7BE43);
+    assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    assert_eq!(
+        crc32(b"The quick brown fox jumps over the lazy dog"),
+        0x414FA339
+    );
+}
+
+#[test]
+fn it_crc() {
+    test_crc("123456789", 9, 0xCBF43926, false);
+    test_crc(
+        "The quick brown fox jumps over the lazy dog",
+        43,
+        0x414FA339,
+        false,
+    );
+
+    // Only the first 5 bytes
+    test_crc("123456789", 5, 0xCBF53A1C, false);
+
+    // Empty range
+    test_crc("123456789", 0, 0, true);
+}
+
+#[test]
+fn it_crc_should_not_wrap() {
+    test_command_without_setup(
+        "tests/asm/crc.asm",
+        "tests/bin/crc.bin",
+        HashMap::from([("string", "123456789"), ("length", "0x2000")]),
+        3,
+        |cpu| {
+            // Jump to error
+            assert_eq!(cpu.pc, 0);
+            assert_eq!(cpu.work_regs[1], 0xC, "R1");
+        },
+    );
+}
+
+fn test_crc(string: &str, length: u32, result: u32, zero: bool) -> CPU {
+    let length = format!("{length:#X}");
+
+    test_command_without_setup(
+        "tests/asm/crc.asm",
+        "tests/bin/crc.bin",
+        HashMap::from([("string", string), ("length", length.as_str())]),
+        3,
+        |cpu| {
+            assert_eq!(cpu.zero, zero, "Zero");
+            assert_eq!(cpu.work_regs[1], result, "R1");
+        },
+    )
+}
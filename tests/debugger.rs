@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use chip32_sim::{
+    breakpoint::Breakpoint,
+    cpu::{HaltState, StepResult},
+    debugger::{Debugger, StopReason},
+    symbols::Symbols,
+};
+use util::{build_and_load, prep_test};
+
+mod util;
+
+#[test]
+fn it_steps_back() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_back.bin");
+    let initial_logs = debugger.state.logs.len();
+
+    // Through hex.w
+    for _ in 0..4 {
+        assert_eq!(debugger.step(), StepResult::Running);
+    }
+
+    assert_eq!(debugger.state.ram.read_word(0x1B00), 0x1234);
+    assert_eq!(debugger.state.logs.len(), initial_logs + 1);
+
+    // Undo hex.w
+    assert_eq!(debugger.step_back(), Some(StepResult::Running));
+    assert_eq!(debugger.state.pc, address(&debugger, "print"));
+    assert_eq!(debugger.state.logs.len(), initial_logs);
+
+    // Undo the store
+    assert_eq!(debugger.step_back(), Some(StepResult::Running));
+    assert_eq!(debugger.state.pc, address(&debugger, "store"));
+    assert_eq!(debugger.state.ram.read_word(0x1B00), 0);
+    // The undone instruction is next
+    assert_eq!(debugger.next_state.ram.read_word(0x1B00), 0x1234);
+
+    // Stepping forward again replays the same instructions
+    debugger.step();
+    debugger.step();
+    assert_eq!(debugger.state.pc, address(&debugger, "print") + 2);
+    assert_eq!(debugger.state.ram.read_word(0x1B00), 0x1234);
+    assert_eq!(debugger.state.logs.len(), initial_logs + 1);
+}
+
+#[test]
+fn it_stops_at_the_start_of_history() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_start.bin");
+
+    assert_eq!(debugger.step_back(), None);
+
+    for _ in 0..4 {
+        debugger.step();
+    }
+
+    assert!(matches!(debugger.reverse_run(), StopReason::HistoryStart));
+    assert_eq!(debugger.state.pc, 0x2);
+    assert_eq!(debugger.state.ram.read_word(0x1B00), 0);
+    assert_eq!(debugger.step_back(), None);
+}
+
+#[test]
+fn it_reverse_runs_to_breakpoints() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_reverse.bin");
+
+    for _ in 0..5 {
+        debugger.step();
+    }
+
+    debugger.breakpoints.push(Breakpoint {
+        address: address(&debugger, "store"),
+        condition: None,
+    });
+
+    assert!(matches!(debugger.reverse_run(), StopReason::Breakpoint(0)));
+    assert_eq!(debugger.state.pc, address(&debugger, "store"));
+    assert_eq!(debugger.state.ram.read_word(0x1B00), 0);
+}
+
+#[test]
+fn it_keeps_watchpoints_when_stepping_back() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_watch.bin");
+
+    for _ in 0..4 {
+        debugger.step();
+    }
+
+    debugger.state.ram.watchpoints = vec!["w:1B00".parse().unwrap()];
+    debugger.refresh();
+
+    // Watchpoints are not undone, and apply to the undone instruction
+    debugger.step_back();
+    assert!(matches!(
+        debugger.step_back(),
+        Some(StepResult::Watchpoint(_))
+    ));
+    assert_eq!(debugger.state.ram.watchpoints.len(), 1);
+    assert_eq!(debugger.next_state.ram.watchpoints.len(), 1);
+
+    assert!(matches!(debugger.step(), StepResult::Watchpoint(_)));
+
+    // Removing it is also not undone
+    debugger.state.ram.watchpoints.clear();
+    debugger.refresh();
+
+    assert_eq!(debugger.step_back(), Some(StepResult::Running));
+    assert!(debugger.state.ram.watchpoints.is_empty());
+    assert_eq!(debugger.step(), StepResult::Running);
+}
+
+#[test]
+fn it_steps_over_calls() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_over.bin");
+    run_to(&mut debugger, "skipped");
+
+    // Not taken
+    assert!(matches!(debugger.step_over(100), StopReason::Reached));
+    assert_eq!(debugger.state.pc, address(&debugger, "called"));
+
+    // Taken, running through both subroutines
+    assert!(matches!(debugger.step_over(100), StopReason::Reached));
+    assert_eq!(debugger.state.pc, address(&debugger, "after"));
+    assert_eq!(debugger.state.sp, 0);
+    assert_eq!(debugger.state.work_regs[2], 0x1235);
+
+    // Not a call
+    assert!(matches!(debugger.step_over(100), StopReason::Reached));
+    assert!(matches!(debugger.state.halt, HaltState::Success));
+    assert!(matches!(debugger.step_over(100), StopReason::Halted));
+}
+
+#[test]
+fn it_stops_stepping_over_at_breakpoints_in_the_callee() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_over_break.bin");
+    run_to(&mut debugger, "called");
+
+    debugger.breakpoints.push(Breakpoint {
+        address: address(&debugger, "leaf"),
+        condition: None,
+    });
+
+    assert!(matches!(debugger.step_over(100), StopReason::Breakpoint(0)));
+    assert_eq!(debugger.state.pc, address(&debugger, "leaf"));
+    assert_eq!(debugger.state.sp, 2);
+}
+
+#[test]
+fn it_stops_stepping_over_when_the_callee_halts() {
+    let mut debugger = load_debugger("exit 0", "tests/bin/debugger_over_halt.bin");
+    run_to(&mut debugger, "called");
+
+    assert!(matches!(debugger.step_over(100), StopReason::Halted));
+    assert!(matches!(debugger.state.halt, HaltState::Success));
+    assert_eq!(debugger.state.sp, 2);
+}
+
+#[test]
+fn it_finishes_subroutines() {
+    // The leaf pushes a value, which must not be mistaken for the return
+    let mut debugger = load_debugger("push r2\npop r3", "tests/bin/debugger_finish.bin");
+    run_to(&mut debugger, "leaf");
+    debugger.step();
+    assert_eq!(debugger.state.sp, 3);
+
+    assert!(matches!(debugger.finish(100), Some(StopReason::Reached)));
+    assert_eq!(debugger.state.pc, address(&debugger, "subroutine_return"));
+    assert_eq!(debugger.state.sp, 1);
+    assert_eq!(debugger.state.work_regs[3], 0x1235);
+
+    assert!(matches!(debugger.finish(100), Some(StopReason::Reached)));
+    assert_eq!(debugger.state.pc, address(&debugger, "after"));
+    assert_eq!(debugger.state.sp, 0);
+
+    // Nothing to return from
+    assert!(debugger.finish(100).is_none());
+}
+
+#[test]
+fn it_stops_finishing_at_breakpoints_and_halts() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_finish_break.bin");
+    run_to(&mut debugger, "subroutine");
+
+    debugger.breakpoints.push(Breakpoint {
+        address: address(&debugger, "leaf"),
+        condition: None,
+    });
+
+    assert!(matches!(
+        debugger.finish(100),
+        Some(StopReason::Breakpoint(0))
+    ));
+    assert_eq!(debugger.state.pc, address(&debugger, "leaf"));
+
+    let mut debugger = load_debugger("exit 0", "tests/bin/debugger_finish_halt.bin");
+    run_to(&mut debugger, "subroutine");
+
+    assert!(matches!(debugger.finish(100), Some(StopReason::Halted)));
+}
+
+#[test]
+fn it_runs_to_addresses() {
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_run_to.bin");
+    let after = address(&debugger, "after");
+
+    debugger.breakpoints.push(Breakpoint {
+        address: address(&debugger, "leaf"),
+        condition: None,
+    });
+
+    // Stops at breakpoints on the way
+    assert!(matches!(
+        debugger.run_to(100, after),
+        StopReason::Breakpoint(0)
+    ));
+    assert_eq!(debugger.state.pc, address(&debugger, "leaf"));
+
+    assert!(matches!(debugger.run_to(100, after), StopReason::Reached));
+    assert_eq!(debugger.state.pc, after);
+
+    // Runs out of instructions
+    let mut debugger = load_debugger("nop", "tests/bin/debugger_run_to_limit.bin");
+    assert!(matches!(debugger.run_to(2, after), StopReason::Limit));
+    assert_eq!(debugger.state.pc, address(&debugger, "store"));
+}
+
+/// Loads debugger.asm with `leaf` as the body of the innermost subroutine, and its symbols
+fn load_debugger(leaf: &str, output_path: &str) -> Debugger {
+    let asm = prep_test("tests/asm/debugger.asm", HashMap::from([("leaf", leaf)]));
+    let cpu = build_and_load(&asm, output_path);
+    let symbols = Symbols::parse_asm(&asm, cpu.ram.bytes());
+
+    Debugger::new(cpu, symbols, None)
+}
+
+fn address(debugger: &Debugger, label: &str) -> u16 {
+    debugger.parse_address(label).unwrap()
+}
+
+fn run_to(debugger: &mut Debugger, label: &str) {
+    let address = address(debugger, label);
+
+    assert!(matches!(debugger.run_to(100, address), StopReason::Reached));
+}
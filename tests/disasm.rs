@@ -0,0 +1,144 @@
+use std::{collections::HashMap, fs};
+
+use chip32_sim::disasm::{decode, disassemble, disassemble_around, Condition, Operand};
+use util::{prep_and_load, test_command_without_setup};
+
+mod util;
+
+fn assemble(output_path: &str) -> Vec<u8> {
+    test_command_without_setup(
+        "tests/asm/disasm.asm",
+        output_path,
+        HashMap::new(),
+        0,
+        |_| {},
+    );
+
+    fs::read(output_path).unwrap_or_else(|_| panic!("Could not read bin file at {output_path}"))
+}
+
+#[test]
+fn it_disassembles_a_listing() {
+    let bytes = assemble("tests/bin/disasm_listing.bin");
+
+    let listing = disassemble(&bytes, 0, bytes.len() as u16)
+        .iter()
+        .map(|instruction| instruction.to_string())
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        listing,
+        vec![
+            "nop",
+            "ld R1,#0x20",
+            "ld R2,#0x12345678",
+            "ld.b R3,(0x1B00)",
+            "ld.w (0x1B02),R4",
+            "ld.l R5,(R6)",
+            "ld.l (R7),R8",
+            "add R1,R2",
+            "cmp R3,#0x5",
+            "asl R1,#0x4",
+            "ror R2,R3",
+            "mul R1,R2",
+            "hex.w R9",
+            "dec.l R10",
+            "printf R2",
+            "push R3",
+            "pop R4",
+            "sec",
+            "clc",
+            "ret nz",
+            "ret",
+            "uivisible R1,R0",
+            "host R0,R1",
+            "close",
+            "queryslot R2",
+            "jp 0x003E",
+            "jp c,0x003E",
+            "call z,0x003E",
+            "exit 1",
+        ]
+    );
+}
+
+#[test]
+fn it_formats_executed_instructions_like_the_listing() {
+    let mut cpu = prep_and_load(
+        "tests/asm/disasm.asm",
+        "tests/bin/disasm_executed.bin",
+        HashMap::new(),
+    );
+    // Loaded into R3, the amount of the register rotate
+    cpu.ram.write_byte(0x1B00, 1);
+
+    // Everything up to the printf
+    for _ in 0..13 {
+        let expected = decode(cpu.ram.bytes(), cpu.pc).to_string();
+
+        cpu.step();
+
+        assert_eq!(cpu.formatted_instruction, expected);
+
+        if expected.starts_with("asl") {
+            // The immediate shift amount is stored minus 1
+            assert_eq!(cpu.formatted_instruction, "asl R1,#0x4");
+        }
+    }
+}
+
+#[test]
+fn it_includes_immediate_words() {
+    let bytes = assemble("tests/bin/disasm_words.bin");
+
+    let instruction = decode(&bytes, 0x6);
+
+    assert_eq!(instruction.address, 0x6);
+    assert_eq!(instruction.words, vec![0x1802, 0x5678, 0x1234]);
+    assert_eq!(instruction.size(), 6);
+    assert_eq!(
+        instruction.operands,
+        vec![Operand::Register(2), Operand::Immediate(0x12345678)]
+    );
+}
+
+#[test]
+fn it_resolves_jump_targets() {
+    let bytes = assemble("tests/bin/disasm_targets.bin");
+
+    let call = decode(&bytes, 0x42);
+
+    assert!(call.is_call());
+    assert_eq!(call.target(), Some(0x3E));
+    assert_eq!(call.operands[0], Operand::Condition(Condition::Zero));
+
+    assert_eq!(decode(&bytes, 0x2).target(), None);
+}
+
+#[test]
+fn it_marks_invalid_instructions() {
+    let instruction = decode(&[0x00, 0x01, 0x00, 0x00], 0);
+
+    assert!(!instruction.is_valid());
+    assert_eq!(instruction.to_string(), "???");
+    assert_eq!(instruction.size(), 2);
+
+    // Reads past the end decode as nop
+    assert_eq!(decode(&[], 0x10).to_string(), "nop");
+}
+
+#[test]
+fn it_disassembles_around_an_address() {
+    let bytes = assemble("tests/bin/disasm_around.bin");
+
+    let addresses = disassemble_around(&bytes, 0x6, 2, 2)
+        .iter()
+        .map(|instruction| instruction.address)
+        .collect::<Vec<u16>>();
+
+    // ld R2,#0x12345678 at 0x6 is 6 bytes long
+    assert_eq!(addresses, vec![0x0, 0x2, 0x6, 0xC, 0x10]);
+
+    // Only as many instructions as exist before the start of memory
+    assert_eq!(disassemble_around(&bytes, 0x2, 4, 0).len(), 2);
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use chip32_sim::cpu::{HaltState, CPU};
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_faults_on_unknown_instruction() {
+    test_fault("0x1200");
+    test_fault("0x3000");
+    test_fault("0x5E00");
+}
+
+#[test]
+fn it_faults_on_bad_identifier() {
+    // hex/dec
+    test_fault("0x4160");
+    // ret
+    test_fault("0x4205");
+    // exit
+    test_fault("0x4602");
+    // clc/sec
+    test_fault("0x4702");
+}
+
+#[test]
+fn it_stops_after_fault() {
+    let cpu = test_command_without_setup(
+        "tests/asm/raw.asm",
+        "tests/bin/raw.bin",
+        HashMap::from([("word", "0x1200")]),
+        3,
+        |_| {},
+    );
+
+    assert_eq!(cpu.pc, 4);
+}
+
+fn test_fault(word: &str) -> CPU {
+    let expected_opcode = u16::from_str_radix(&word[2..], 16).unwrap();
+
+    test_command_without_setup(
+        "tests/asm/raw.asm",
+        "tests/bin/raw.bin",
+        HashMap::from([("word", word)]),
+        1,
+        |cpu| {
+            if let HaltState::Fault { pc, opcode, .. } = cpu.halt {
+                assert_eq!(pc, 2, "PC");
+                assert_eq!(opcode, expected_opcode, "Opcode");
+            } else {
+                panic!("CPU did not fault");
+            }
+        },
+    )
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use chip32_sim::{cpu::CPU, util::rng::Rng};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_xfills() {
+    // Fill byte is the upper byte, length is the lower 24 bits
+    let cpu = test_fill("xfill", "0x1000", "0xAB000010", |_| {});
+    assert_eq!(cpu.bridge.read_byte(0x0FFF), 0);
+    assert_eq!(cpu.bridge.read_byte(0x1000), 0xAB);
+    assert_eq!(cpu.bridge.read_byte(0x100F), 0xAB);
+    assert_eq!(cpu.bridge.read_byte(0x1010), 0);
+
+    // Crosses a page boundary
+    let cpu = test_fill("xfill", "0xFFFFF0", "0x5A000020", |_| {});
+    assert_eq!(cpu.bridge.read_bytes(0xFFFFF0, 0x20), vec![0x5A; 0x20]);
+    assert_eq!(cpu.bridge.read_byte(0x1000010), 0);
+}
+
+#[test]
+fn it_xfills_with_zero() {
+    let cpu = test_fill("xfill", "0x1000", "0x00000008", |cpu| {
+        cpu.bridge.fill(0x1000, 0x10, 0xFF);
+    });
+    assert_eq!(cpu.bridge.read_bytes(0x1000, 0x8), vec![0; 0x8]);
+    assert_eq!(cpu.bridge.read_bytes(0x1008, 0x8), vec![0xFF; 0x8]);
+}
+
+#[test]
+fn it_rfills_deterministically() {
+    let first = test_fill("rfill", "0x2000", "0x100", |cpu| cpu.rng = Rng::new(1234));
+    let second = test_fill("rfill", "0x2000", "0x100", |cpu| cpu.rng = Rng::new(1234));
+    let other_seed = test_fill("rfill", "0x2000", "0x100", |cpu| cpu.rng = Rng::new(4321));
+
+    let data = first.bridge.read_bytes(0x2000, 0x100);
+    assert_eq!(data, second.bridge.read_bytes(0x2000, 0x100));
+    assert_ne!(data, other_seed.bridge.read_bytes(0x2000, 0x100));
+    assert!(data.iter().any(|byte| *byte != 0));
+
+    assert_eq!(first.bridge.read_byte(0x1FFF), 0);
+    assert_eq!(first.bridge.read_byte(0x2100), 0);
+}
+
+#[test]
+fn it_rejects_out_of_range_fills() {
+    // Longer than the 24 bit length xfill can express
+    let cpu = test_fill("rfill", "0x1000", "0x1000000", |_| {});
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+    assert_eq!(
+        cpu.logs.last().unwrap(),
+        "Sim: rfill of length 0x1000000 from 0x1000 is out of range"
+    );
+
+    // Past the end of the bridge
+    let cpu = test_fill("rfill", "0xFFFFFF00", "0x200", |_| {});
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    let cpu = test_fill("xfill", "0xFFFFFF00", "0xAB000200", |_| {});
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    // Up to the end of the bridge is allowed
+    let cpu = test_fill("rfill", "0xFFFFFF00", "0x100", |_| {});
+    assert_ne!(cpu.pc, 0);
+    assert_eq!(cpu.bridge.read_byte(0x0), 0);
+}
+
+fn test_fill<T: FnOnce(&mut CPU)>(command: &str, address: &str, value: &str, setup: T) -> CPU {
+    test_command(
+        "tests/asm/fill.asm",
+        &format!("tests/bin/{command}.bin"),
+        HashMap::from([("command", command), ("address", address), ("value", value)]),
+        3,
+        setup,
+        |_| {},
+    )
+}
@@ -0,0 +1,62 @@
+use std::{collections::HashMap, time::Instant};
+
+use chip32_sim::{
+    clock::{ClockMode, CYCLES_PER_MILLISECOND},
+    cpu::CPU,
+};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_gets_virtual_time() {
+    let cpu = test_gettime(|_| {});
+    assert_eq!(cpu.work_regs[1], 0);
+
+    let cpu = test_gettime(|cpu| cpu.cycles = 5 * CYCLES_PER_MILLISECOND);
+    assert_eq!(cpu.work_regs[1], 5);
+
+    // Wraps at 32 bits
+    let cpu = test_gettime(|cpu| cpu.cycles = (1 << 32) * CYCLES_PER_MILLISECOND);
+    assert_eq!(cpu.work_regs[1], 0);
+}
+
+#[test]
+fn it_gets_fixed_time() {
+    let cpu = test_gettime(|cpu| {
+        cpu.clock = ClockMode::Fixed(1234);
+        cpu.cycles = 5 * CYCLES_PER_MILLISECOND;
+    });
+    assert_eq!(cpu.work_regs[1], 1234);
+}
+
+#[test]
+fn it_gets_host_time() {
+    let start = Instant::now();
+    let cpu = test_gettime(|cpu| cpu.clock = ClockMode::Host(start));
+
+    assert!(cpu.work_regs[1] as u128 <= start.elapsed().as_millis());
+}
+
+#[test]
+fn it_parses_clock_modes() {
+    assert!(matches!("virtual".parse(), Ok(ClockMode::Virtual)));
+    assert!(matches!("host".parse(), Ok(ClockMode::Host(_))));
+    assert!(matches!("100".parse(), Ok(ClockMode::Fixed(100))));
+    assert!("later".parse::<ClockMode>().is_err());
+}
+
+fn test_gettime<T: FnOnce(&mut CPU)>(setup: T) -> CPU {
+    test_command(
+        "tests/asm/gettime.asm",
+        "tests/bin/gettime.bin",
+        HashMap::new(),
+        1,
+        |cpu| {
+            // Make sure the previous value is overwritten
+            cpu.work_regs[1] = 0xDEADBEEF;
+            setup(cpu);
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,130 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use chip32_sim::{
+    cpu::CPU,
+    debugger::Debugger,
+    host::{HostHandler, RecordingHostHandler, HOST_INIT, HOST_RESET, HOST_RUN},
+    symbols::Symbols,
+};
+use util::{prep_and_load, test_command};
+
+mod util;
+
+#[test]
+fn it_accepts_host_commands_by_default() {
+    let cpu = test_host(HOST_RUN, 0x1234, |_| {});
+
+    assert!(cpu.zero);
+    assert!(cpu
+        .logs
+        .iter()
+        .any(|log| log.contains("host_run (0x4001)") && log.contains("0x1234")));
+}
+
+#[test]
+fn it_records_host_commands() {
+    let handler = Rc::new(RefCell::new(RecordingHostHandler::default()));
+
+    let cpu = test_host(HOST_INIT, 0x20, |cpu| cpu.host = handler.clone());
+    assert!(cpu.zero);
+
+    let handler = handler.borrow();
+    assert_eq!(handler.calls.len(), 1);
+    assert_eq!(handler.calls[0].command, HOST_INIT);
+    assert_eq!(handler.calls[0].parameter, 0x20);
+    assert!(handler.calls[0].accepted);
+}
+
+#[test]
+fn it_rejects_host_commands() {
+    let handler = Rc::new(RefCell::new(RecordingHostHandler {
+        rejected_commands: vec![HOST_RESET],
+        ..Default::default()
+    }));
+
+    let cpu = test_host(HOST_RESET, 0, |cpu| cpu.host = handler.clone());
+    assert!(!cpu.zero);
+    assert!(!handler.borrow().calls[0].accepted);
+
+    // Other commands still succeed
+    let cpu = test_host(HOST_RUN, 0, |cpu| cpu.host = handler.clone());
+    assert!(cpu.zero);
+}
+
+#[test]
+fn it_scripts_host_responses() {
+    let handler: Rc<RefCell<dyn HostHandler>> =
+        Rc::new(RefCell::new(|command: u32, parameter: u32| {
+            if command == 0x5000 && parameter > 0x10 {
+                Err("Parameter out of range".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+
+    let cpu = test_host(0x5000, 0x10, |cpu| cpu.host = handler.clone());
+    assert!(cpu.zero);
+
+    let cpu = test_host(0x5000, 0x11, |cpu| cpu.host = handler.clone());
+    assert!(!cpu.zero);
+    assert!(cpu
+        .logs
+        .iter()
+        .any(|log| log.contains("Parameter out of range")));
+}
+
+#[test]
+fn it_sends_host_commands_once_when_debugging() {
+    let handler = Rc::new(RefCell::new(RecordingHostHandler {
+        rejected_commands: vec![HOST_RESET],
+        ..Default::default()
+    }));
+
+    let mut cpu = prep_and_load(
+        "tests/asm/host.asm",
+        "tests/bin/host_debugger.bin",
+        HashMap::from([("command", "0x4000"), ("parameter", "0")]),
+    );
+    cpu.zero = true;
+    cpu.host = handler.clone();
+
+    let mut debugger = Debugger::new(cpu, Symbols::default(), None);
+
+    // Looking ahead to the host command doesn't send it
+    debugger.step();
+    debugger.step();
+    debugger.refresh();
+    assert!(handler.borrow().calls.is_empty());
+
+    debugger.step();
+    assert_eq!(handler.borrow().calls.len(), 1);
+    // The reply from the handler is used
+    assert!(!debugger.state.zero);
+
+    // Replaying after stepping back doesn't send it again
+    debugger.step_back();
+    debugger.step();
+    assert_eq!(handler.borrow().calls.len(), 1);
+    assert!(!debugger.state.zero);
+}
+
+fn test_host<T: FnOnce(&mut CPU)>(command: u32, parameter: u32, setup: T) -> CPU {
+    let command = format!("{command:#X}");
+    let parameter = format!("{parameter:#X}");
+
+    test_command(
+        "tests/asm/host.asm",
+        "tests/bin/host.bin",
+        HashMap::from([
+            ("command", command.as_str()),
+            ("parameter", parameter.as_str()),
+        ]),
+        3,
+        |cpu| {
+            // Make sure the flag is overwritten
+            cpu.zero = false;
+            setup(cpu);
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,53 @@
+use std::{collections::HashMap, fs};
+
+use chip32_sim::{apf::parse_json, cpu::CPU};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_loads_file_at_slot_address() {
+    let cpu = test_loadf("0x21");
+    assert!(cpu.zero);
+
+    let image = fs::read("example/ex_image_2.bin").unwrap();
+    assert_eq!(cpu.bridge.read_bytes(0x12345678, image.len()), image);
+    assert_eq!(cpu.bridge.read_byte(0x12345677), 0);
+    assert_eq!(cpu.bridge.read_byte(0x12345678 + image.len() as u32), 0);
+}
+
+#[test]
+fn it_loadf_should_fail() {
+    // Exceeds size_maximum
+    let cpu = test_loadf("0x22");
+    assert!(!cpu.zero);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    // File doesn't exist
+    let cpu = test_loadf("0x23");
+    assert!(!cpu.zero);
+
+    // No address
+    let cpu = test_loadf("0x20");
+    assert!(!cpu.zero);
+    assert_eq!(cpu.bridge.allocated_size(), 0);
+
+    // No slot
+    let cpu = test_loadf("0x99");
+    assert!(!cpu.zero);
+}
+
+fn test_loadf(slot: &str) -> CPU {
+    test_command(
+        "tests/asm/loadf.asm",
+        "tests/bin/loadf.bin",
+        HashMap::from([("slot", slot)]),
+        2,
+        |cpu| {
+            cpu.file_state.slots = parse_json("tests/data.json");
+            // Start from success so failures are observable
+            cpu.zero = true;
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use chip32_sim::{apf::parse_json, cpu::CPU};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_queries_slots() {
+    // Slot, whether the query succeeds
+    let matrix = [
+        // Present slots
+        ("0", true),
+        ("0x20", true),
+        ("0x21", true),
+        // Missing slot
+        ("0x99", false),
+        // Missing file
+        ("0x23", false),
+        // Deferred slot with file
+        ("0x24", true),
+        // Deferred slot without file
+        ("0x25", false),
+    ];
+
+    for (slot, present) in matrix {
+        let cpu = test_queryslot(slot, |_| {});
+        assert_eq!(cpu.zero, present, "Slot {slot}");
+
+        // The previous flag value is overwritten
+        let cpu = test_queryslot(slot, |cpu| cpu.zero = !present);
+        assert_eq!(cpu.zero, present, "Slot {slot}");
+    }
+}
+
+#[test]
+fn it_queries_adjusted_slots() {
+    // Offset past the end of the file
+    let cpu = test_queryslot("0x21", |cpu| {
+        cpu.file_state.adjustments.entry(0x21).or_default().offset = Some(0x10000000);
+    });
+    assert!(!cpu.zero);
+
+    // The reported length honors the adjustments
+    let cpu = test_queryslot("0x24", |cpu| {
+        let adjustment = cpu.file_state.adjustments.entry(0x24).or_default();
+        adjustment.offset = Some(0x30);
+        adjustment.size = Some(0x20);
+    });
+    assert!(cpu.zero);
+    assert!(cpu
+        .logs
+        .iter()
+        .any(|log| log.ends_with("present with length 0x10")));
+}
+
+fn test_queryslot<T: FnOnce(&mut CPU)>(slot: &str, setup: T) -> CPU {
+    test_command(
+        "tests/asm/queryslot.asm",
+        "tests/bin/queryslot.bin",
+        HashMap::from([("slot", slot)]),
+        2,
+        |cpu| {
+            cpu.file_state.slots = parse_json("tests/data.json");
+            setup(cpu);
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use chip32_sim::{
+    cpu::{HaltState, CPU},
+    mem::{BoundsPolicy, RomWritePolicy},
+};
+use util::test_command;
+
+mod util;
+
+#[test]
+fn it_warns_on_rom_writes() {
+    let cpu = test_rom_write("ld.w (r2),r1", "0x2", RomWritePolicy::Warn);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.ram.read_word(0x2), 0xBEEF);
+    assert!(cpu
+        .logs
+        .iter()
+        .any(|log| log == "ERROR: Clobbering ROM data at 0x0002 (2 byte(s), PC 0x0006)"));
+}
+
+#[test]
+fn it_allows_rom_writes() {
+    let cpu = test_rom_write("ld.w (r2),r1", "0x2", RomWritePolicy::Allow);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.ram.read_word(0x2), 0xBEEF);
+    assert!(!cpu.logs.iter().any(|log| log.contains("ROM")));
+}
+
+#[test]
+fn it_faults_on_rom_writes() {
+    let cpu = test_rom_write("ld.l (r2),r1", "0x4", RomWritePolicy::Fault);
+    assert!(matches!(cpu.halt, HaltState::Fault { pc: 0x6, .. }));
+    // The write is skipped
+    assert_ne!(cpu.ram.read_word(0x4), 0xBEEF);
+}
+
+#[test]
+fn it_ignores_writes_outside_rom() {
+    let cpu = test_rom_write("ld.w (r2),r1", "0x100", RomWritePolicy::Fault);
+    assert!(matches!(cpu.halt, HaltState::Running));
+    assert_eq!(cpu.ram.read_word(0x100), 0xBEEF);
+
+    // Writes that wrap into ROM are caught
+    let cpu = test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/rom.bin",
+        HashMap::from([("command", "ld.l (r2),r1"), ("address", "0x1FFE")]),
+        2,
+        |cpu| {
+            cpu.ram.bounds_policy = BoundsPolicy::Wrap;
+            cpu.ram.rom_write_policy = RomWritePolicy::Fault;
+            cpu.work_regs[1] = 0xDEADBEEF;
+        },
+        |_| {},
+    );
+    assert!(matches!(cpu.halt, HaltState::Fault { .. }));
+}
+
+fn test_rom_write(command: &str, address: &str, policy: RomWritePolicy) -> CPU {
+    test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/rom.bin",
+        HashMap::from([("command", command), ("address", address)]),
+        2,
+        |cpu| {
+            cpu.ram.rom_write_policy = policy;
+            cpu.work_regs[1] = 0xDEADBEEF;
+        },
+        |_| {},
+    )
+}
@@ -0,0 +1,56 @@
+use std::fs;
+
+use chip32_sim::{assembler::assemble, source::SourceMap};
+
+fn load_example() -> SourceMap {
+    let bytes = fs::read("example/example_chip32.bin").expect("Could not read example bin");
+
+    SourceMap::load("example/example_chip32.asm", &bytes).expect("Could not load example source")
+}
+
+#[test]
+fn it_maps_addresses_to_lines() {
+    let source = load_example();
+
+    assert_eq!(source.name, "example_chip32.asm");
+
+    // jp error
+    assert_eq!(source.line_at(0x0), Some(37));
+    // cmp r0,#datslot
+    assert_eq!(source.line_at(0x2), Some(41));
+    // jp z,loaddata, after the 4 byte cmp
+    assert_eq!(source.line_at(0x6), Some(42));
+    // ld r3,#datslot
+    assert_eq!(source.line_at(0x16), Some(52));
+
+    // The immediate of the cmp
+    assert_eq!(source.line_at(0x4), None);
+    assert_eq!(source.line_of(0x4), Some(41));
+}
+
+#[test]
+fn it_maps_lines_to_addresses() {
+    let source = load_example();
+
+    assert_eq!(source.address_of(52), Some(0x16));
+    // Blank lines and labels resolve to the next statement
+    assert_eq!(source.address_of(50), Some(0x16));
+    assert_eq!(source.address_of(10_000), None);
+
+    assert_eq!(source.symbols.lookup("was_loaded"), Some(0x16));
+    assert_eq!(source.symbols.lookup("file_err"), Some(0x13C));
+}
+
+#[test]
+fn it_keeps_separators_inside_strings() {
+    let text = "start:\n  db \"a;b//c\\\"\",0 // comment; with separators\nafter: exit 0\n";
+    let bytes = assemble(text).expect("Could not assemble");
+
+    let source = SourceMap::parse(text, &bytes);
+
+    assert_eq!(source.line_at(0x0), Some(2));
+    // 7 characters in the string and the terminator
+    assert_eq!(source.symbols.lookup("after"), Some(0x8));
+    assert_eq!(source.line_at(0x8), Some(3));
+    assert_eq!(source.entries.len(), 2);
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use chip32_sim::cpu::CPU;
+use chip32_sim::cpu::{HaltState, CPU, DEFAULT_STACK_DEPTH};
 use util::test_command;
 
 mod util;
@@ -100,6 +100,59 @@ fn it_call() {
     assert_eq!(cpu.stack[0], 0x0);
 }
 
+#[test]
+fn it_push_overflow() {
+    let cpu = test_push(30);
+    assert_eq!(cpu.pc, 0x8);
+    assert_eq!(cpu.sp, 31);
+    assert_eq!(cpu.stack[30], 0x20);
+
+    // Stack is full
+    let cpu = test_push(31);
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.sp, 31);
+    assert_eq!(cpu.error_pc_reg, 0x8);
+}
+
+#[test]
+fn it_call_overflow() {
+    let cpu = test_command(
+        "tests/asm/stack_jump.asm",
+        "tests/bin/call_overflow.bin",
+        HashMap::from([("command", "call"), ("targets", "0x10")]),
+        1,
+        |cpu| cpu.sp = cpu.stack.len(),
+        |_| {},
+    );
+    assert_eq!(cpu.pc, 0);
+    assert_eq!(cpu.sp, DEFAULT_STACK_DEPTH);
+}
+
+#[test]
+fn it_recurses() {
+    // Each level pushes r1 and calls the next, except the last, plus one entry for the initial call
+    let cpu = test_recursion(15, DEFAULT_STACK_DEPTH);
+    assert!(matches!(cpu.halt, HaltState::Success));
+    assert_eq!(cpu.sp, 0);
+    assert_eq!(cpu.work_regs[1], 15);
+
+    let cpu = test_recursion(16, DEFAULT_STACK_DEPTH);
+    assert!(matches!(cpu.halt, HaltState::Failure));
+}
+
+#[test]
+fn it_recurses_with_configured_depth() {
+    let cpu = test_recursion(100, 200);
+    assert!(matches!(cpu.halt, HaltState::Success));
+    assert_eq!(cpu.work_regs[1], 100);
+
+    let cpu = test_recursion(100, 199);
+    assert!(matches!(cpu.halt, HaltState::Failure));
+
+    let cpu = test_recursion(2, 3);
+    assert!(matches!(cpu.halt, HaltState::Failure));
+}
+
 fn test_stack(
     command: &str,
     target: &str,
@@ -130,3 +183,27 @@ fn test_stack(
         },
     )
 }
+
+fn test_push(initial_sp: usize) -> CPU {
+    test_command(
+        "tests/asm/push.asm",
+        "tests/bin/push.bin",
+        HashMap::new(),
+        2,
+        |cpu| cpu.sp = initial_sp,
+        |_| {},
+    )
+}
+
+fn test_recursion(depth: u32, stack_depth: usize) -> CPU {
+    let depth = depth.to_string();
+
+    test_command(
+        "tests/asm/recursion.asm",
+        "tests/bin/recursion.bin",
+        HashMap::from([("depth", depth.as_str())]),
+        10_000,
+        |cpu| cpu.stack = vec![0; stack_depth],
+        |_| {},
+    )
+}
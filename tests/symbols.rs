@@ -0,0 +1,75 @@
+use chip32_sim::{
+    disasm::decode,
+    symbols::{SymbolKind, Symbols},
+};
+
+const SOURCE: &str = r#"
+architecture chip32.vm
+
+constant rambuf = 0x1b00
+constant buffer = rambuf+4
+
+// Error vector (0x0)
+                nop
+loop:           ld r1,#buffer       // load: the buffer
+                jp loop
+msg:            db "Hi: ",0
+end:
+"#;
+
+// nop, ld r1,#0x1B04, jp 0x2, "Hi: ", 0
+const BYTES: [u8; 13] = [
+    0x00, 0x00, 0x01, 0x08, 0x04, 0x1B, 0x01, 0x60, b'H', b'i', b':', b' ', 0x00,
+];
+
+#[test]
+fn it_parses_symbol_table_sections() {
+    let symbols =
+        Symbols::parse("[labels]\n00000042 loop\n\n[constants]\n00001B00 rambuf\n").unwrap();
+
+    assert_eq!(symbols.symbols[0].kind, SymbolKind::Label);
+    assert_eq!(symbols.symbols[1].kind, SymbolKind::Constant);
+    assert_eq!(symbols.lookup("rambuf"), Some(0x1B00));
+
+    assert!(Symbols::parse("loop").is_err());
+}
+
+#[test]
+fn it_parses_asm_source() {
+    let symbols = Symbols::parse_asm(SOURCE, &BYTES);
+
+    assert_eq!(symbols.lookup("rambuf"), Some(0x1B00));
+    assert_eq!(symbols.lookup("buffer"), Some(0x1B04));
+    assert_eq!(symbols.lookup("loop"), Some(0x2));
+    assert_eq!(symbols.lookup("msg"), Some(0x8));
+    assert_eq!(symbols.lookup("end"), Some(0xD));
+
+    assert_eq!(symbols.label_at(0x2), Some("loop"));
+    assert_eq!(symbols.label_at(0x4), None);
+
+    // Constants are named, but aren't labels
+    assert_eq!(symbols.name_of(0x1B04), Some("buffer"));
+    assert_eq!(symbols.label_at(0x1B04), None);
+}
+
+#[test]
+fn it_describes_addresses() {
+    let symbols = Symbols::parse_asm(SOURCE, &BYTES);
+
+    assert_eq!(symbols.describe(0x0), None);
+    assert_eq!(symbols.describe(0x2).as_deref(), Some("loop"));
+    assert_eq!(symbols.describe(0x6).as_deref(), Some("loop+0x4"));
+    assert_eq!(symbols.format_address(0x9), "0x0009 <msg+0x1>");
+}
+
+#[test]
+fn it_formats_instructions_symbolically() {
+    let symbols = Symbols::parse_asm(SOURCE, &BYTES);
+
+    assert_eq!(
+        decode(&BYTES, 0x2).to_symbolic_string(&symbols),
+        "ld R1,#buffer"
+    );
+    assert_eq!(decode(&BYTES, 0x6).to_symbolic_string(&symbols), "jp loop");
+    assert_eq!(decode(&BYTES, 0x0).to_symbolic_string(&symbols), "nop");
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_counts_instruction_cycles() {
+    test_command_without_setup(
+        "tests/asm/alu.asm",
+        "tests/bin/timing.bin",
+        HashMap::from([
+            ("command", "add"),
+            ("targets", "r1,r2"),
+            ("r1value", "0x10"),
+            ("r2value", "0xDEADBEEF"),
+        ]),
+        3,
+        |cpu| {
+            // ld r1,#16 (2) | ld r2,#32 (3) | add r1,r2 (1)
+            assert_eq!(cpu.cycles, 6);
+        },
+    );
+}
+
+#[test]
+fn it_counts_transfer_cycles() {
+    test_command_without_setup(
+        "tests/asm/fill.asm",
+        "tests/bin/timing.bin",
+        HashMap::from([
+            ("command", "xfill"),
+            ("address", "0x10"),
+            ("value", "0xAB000100"),
+        ]),
+        3,
+        |cpu| {
+            // ld r1,#16 (2) | ld r2,#32 (3) | xfill r1,r2 (4, plus 1 per byte)
+            assert_eq!(cpu.cycles, 2 + 3 + 4 + 0x100);
+        },
+    );
+}
+
+#[test]
+fn it_does_not_count_halted_cycles() {
+    test_command_without_setup(
+        "tests/asm/raw.asm",
+        "tests/bin/timing.bin",
+        HashMap::from([("word", "0x4600")]),
+        5,
+        |cpu| {
+            // exit
+            assert_eq!(cpu.cycles, 2);
+        },
+    );
+}
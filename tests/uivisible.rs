@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use chip32_sim::cpu::CPU;
+use util::test_command_without_setup;
+
+mod util;
+
+#[test]
+fn it_sets_ui_visibility() {
+    let cpu = test_uivisible("0x10", "1", 3);
+    assert_eq!(cpu.ui.visibility.get(&0x10), Some(&true));
+    assert_eq!(cpu.ui.visibility.len(), 1);
+
+    let cpu = test_uivisible("0x10", "0", 3);
+    assert_eq!(cpu.ui.visibility.get(&0x10), Some(&false));
+
+    // Any nonzero value is visible
+    let cpu = test_uivisible("0x10", "0x100", 3);
+    assert_eq!(cpu.ui.visibility.get(&0x10), Some(&true));
+}
+
+#[test]
+fn it_tracks_ui_elements_separately() {
+    let cpu = test_uivisible("0x10", "0", 6);
+    assert_eq!(cpu.ui.visibility.get(&0x10), Some(&false));
+    assert_eq!(cpu.ui.visibility.get(&0x20), Some(&true));
+
+    // Later calls replace the visibility of the same element
+    let cpu = test_uivisible("0x20", "0", 6);
+    assert_eq!(cpu.ui.visibility.get(&0x20), Some(&true));
+    assert_eq!(cpu.ui.visibility.len(), 1);
+}
+
+fn test_uivisible(element: &str, visible: &str, steps: u32) -> CPU {
+    test_command_without_setup(
+        "tests/asm/uivisible.asm",
+        "tests/bin/uivisible.bin",
+        HashMap::from([("element", element), ("visible", visible)]),
+        steps,
+        |_| {},
+    )
+}
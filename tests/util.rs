@@ -1,6 +1,6 @@
 use std::{collections::HashMap, env, fs, process::Command};
 
-use chip32_sim::cpu::CPU;
+use chip32_sim::{assembler::assemble, cpu::CPU};
 use regex::Regex;
 
 // Testing
@@ -51,9 +51,8 @@ pub fn tmp_path() -> String {
     "tests/chip32-tmp.asm".to_string()
 }
 
-pub fn prep_test(asm_path: &str, replacements: HashMap<&str, &str>) {
-    let tmp_path = tmp_path();
-
+/// Reads the asm file, substituting `{name}` placeholders with their replacements
+pub fn prep_test(asm_path: &str, replacements: HashMap<&str, &str>) -> String {
     let mut asm = fs::read_to_string(asm_path).expect(&format!("Unable to read {asm_path}"));
 
     // TODO: This is naive
@@ -63,14 +62,26 @@ pub fn prep_test(asm_path: &str, replacements: HashMap<&str, &str>) {
         asm = match_regex.replace_all(&asm, replacement).to_string();
     }
 
-    fs::write(&tmp_path, asm).expect(&format!("Unable to write to {tmp_path}"));
+    asm
 }
 
-pub fn build_and_load(asm_path: &str, output_path: &str) -> CPU {
-    let bass_path = env::var("BASS_PATH").expect("No BASS_PATH envvar found");
+/// Assembles with bass if `BASS_PATH` is set, and with the built in assembler otherwise
+pub fn build_and_load(asm: &str, output_path: &str) -> CPU {
+    let Ok(bass_path) = env::var("BASS_PATH") else {
+        let bytes = assemble(asm).unwrap_or_else(|err| panic!("Compilation failed: {err}"));
+
+        fs::write(output_path, &bytes)
+            .unwrap_or_else(|_| panic!("Unable to write to {output_path}"));
+
+        // Loaded from memory, as tests running in parallel may share an output path
+        return CPU::load_bytes(bytes, None, None);
+    };
+
+    let tmp_path = tmp_path();
+    fs::write(&tmp_path, asm).expect(&format!("Unable to write to {tmp_path}"));
 
     let output = Command::new(bass_path)
-        .args(["-strict", asm_path, "-o", output_path])
+        .args(["-strict", &tmp_path, "-o", output_path])
         .output()
         .expect("Compilation failed");
 
@@ -81,6 +92,6 @@ pub fn build_and_load(asm_path: &str, output_path: &str) -> CPU {
 }
 
 pub fn prep_and_load(asm_path: &str, output_path: &str, replacements: HashMap<&str, &str>) -> CPU {
-    prep_test(asm_path, replacements);
-    build_and_load(&tmp_path(), output_path)
+    let asm = prep_test(asm_path, replacements);
+    build_and_load(&asm, output_path)
 }
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use chip32_sim::{
+    cpu::{StepResult, CPU},
+    mem::{WatchKind, Watchpoint, WatchpointHit},
+};
+use util::{prep_and_load, test_command};
+
+mod util;
+
+#[test]
+fn it_stops_on_write_watchpoints() {
+    let (cpu, result) = test_watch("ld.w (r2),r1", "0x1B00", "w:1B00");
+    assert_eq!(
+        result,
+        StepResult::Watchpoint(WatchpointHit {
+            index: 0,
+            address: 0x1B00,
+            length: 2,
+            write: true,
+        })
+    );
+    // The write is still performed
+    assert_eq!(cpu.ram.read_word(0x1B00), 0xBEEF);
+
+    // Overlapping the end of the range
+    let (_, result) = test_watch("ld.l (r2),r1", "0x1AFE", "w:1B00-1BFF");
+    assert!(matches!(result, StepResult::Watchpoint(_)));
+
+    // Reads are ignored
+    let (_, result) = test_watch("ld.w r1,(r2)", "0x1B00", "w:1B00");
+    assert_eq!(result, StepResult::Running);
+
+    // Outside of the range
+    let (_, result) = test_watch("ld.w (r2),r1", "0x1AFE", "w:1B00-1BFF");
+    assert_eq!(result, StepResult::Running);
+}
+
+#[test]
+fn it_stops_on_read_watchpoints() {
+    let (_, result) = test_watch("ld.b r1,(r2)", "0x1B10", "r:1B00-1BFF");
+    assert!(matches!(
+        result,
+        StepResult::Watchpoint(WatchpointHit { write: false, .. })
+    ));
+
+    let (_, result) = test_watch("ld.b (r2),r1", "0x1B10", "r:1B00-1BFF");
+    assert_eq!(result, StepResult::Running);
+}
+
+#[test]
+fn it_stops_on_access_watchpoints() {
+    let (_, result) = test_watch("ld.b r1,(r2)", "0x1B10", "1B00-1BFF");
+    assert!(matches!(result, StepResult::Watchpoint(_)));
+
+    let (_, result) = test_watch("ld.b (r2),r1", "0x1B10", "rw:1B00-1BFF");
+    assert!(matches!(result, StepResult::Watchpoint(_)));
+}
+
+#[test]
+fn it_ignores_instruction_fetches() {
+    let (_, result) = test_watch("ld.b (r2),r1", "0x1B10", "0-10");
+    assert_eq!(result, StepResult::Running);
+}
+
+#[test]
+fn it_ignores_accesses_outside_of_steps() {
+    let mut cpu = prep_and_load(
+        "tests/asm/bounds.asm",
+        "tests/bin/watchpoint_outside.bin",
+        HashMap::from([("command", "nop"), ("address", "0x1B00")]),
+    );
+
+    cpu.ram.watchpoints = vec!["r:1B00".parse().unwrap()];
+
+    // Such as a debugger displaying memory
+    cpu.ram.read_byte(0x1B00);
+
+    assert_eq!(cpu.step(), StepResult::Running);
+    assert_eq!(cpu.step(), StepResult::Running);
+}
+
+#[test]
+fn it_logs_watchpoint_hits() {
+    test_command(
+        "tests/asm/bounds.asm",
+        "tests/bin/watchpoint.bin",
+        HashMap::from([("command", "ld.w (r2),r1"), ("address", "0x1B00")]),
+        2,
+        |cpu| cpu.ram.watchpoints = vec!["w:1B00".parse().unwrap()],
+        |cpu| {
+            assert!(cpu
+                .logs
+                .iter()
+                .any(|log| log
+                    == "Sim: Watchpoint 0 hit by write of 2 byte(s) at 0x1B00 (PC 0x0006)"));
+        },
+    );
+}
+
+#[test]
+fn it_parses_watchpoints() {
+    assert_eq!(
+        "w:0x1B00-0x1BFF".parse(),
+        Ok(Watchpoint {
+            start: 0x1B00,
+            end: 0x1BFF,
+            kind: WatchKind::Write,
+        })
+    );
+    assert_eq!(
+        "r:1b00".parse(),
+        Ok(Watchpoint {
+            start: 0x1B00,
+            end: 0x1B00,
+            kind: WatchKind::Read,
+        })
+    );
+    assert!("x:1B00".parse::<Watchpoint>().is_err());
+    assert!("w:1BFF-1B00".parse::<Watchpoint>().is_err());
+    assert!("w:nowhere".parse::<Watchpoint>().is_err());
+}
+
+/// Runs the setup instruction, then returns the result of `command`
+fn test_watch(command: &str, address: &str, watchpoint: &str) -> (CPU, StepResult) {
+    let mut cpu = prep_and_load(
+        "tests/asm/bounds.asm",
+        "tests/bin/watchpoint.bin",
+        HashMap::from([("command", command), ("address", address)]),
+    );
+
+    cpu.work_regs[1] = 0xDEADBEEF;
+    cpu.ram.watchpoints = vec![watchpoint.parse().unwrap()];
+
+    assert_eq!(cpu.step(), StepResult::Running);
+    let result = cpu.step();
+
+    cpu.logs.iter().for_each(|log| println!("{log}"));
+
+    (cpu, result)
+}